@@ -1,5 +1,6 @@
 // Copyright Epic Games, Inc. All Rights Reserved.
 
+using System.IO;
 using UnrealBuildTool;
 
 public class BPRust : ModuleRules
@@ -17,6 +18,8 @@ public class BPRust : ModuleRules
 		
 		PrivateIncludePaths.AddRange(
 			new string[] {
+				// `ffi.h` of bprust-sys, the structs the plugin passes to Rust are checked against it
+				Path.Combine(PluginDirectory, "..", "bprust-sys"),
 				// ... add other private include paths required here ...
 			}
 			);
@@ -26,6 +29,7 @@ public class BPRust : ModuleRules
 			new string[]
 			{
 				"Core",
+				"CoreUObject",
 				"BPRustLibrary",
 				"Projects"
 				// ... add other public dependencies that you statically link with here ...
@@ -36,6 +40,7 @@ public class BPRust : ModuleRules
 		PrivateDependencyModuleNames.AddRange(
 			new string[]
 			{
+				"Engine",
 				// ... add private dependencies that you statically link with here ...	
 			}
 			);
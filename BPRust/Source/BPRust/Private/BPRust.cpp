@@ -1,15 +1,44 @@
 // Copyright Epic Games, Inc. All Rights Reserved.
 
 #include "BPRust.h"
+#include "BPRustFFI.h"
+#include "BPRustFunctionTable.h"
 #include "Misc/MessageDialog.h"
 #include "Modules/ModuleManager.h"
 #include "Interfaces/IPluginManager.h"
 #include "Misc/Paths.h"
 #include "HAL/PlatformProcess.h"
-#include "BPRustLibrary/ExampleLibrary.h"
+#include "HAL/FileManager.h"
+#include "HAL/IConsoleManager.h"
 
 #define LOCTEXT_NAMESPACE "FBPRustModule"
 
+// Mirrors `BPRustLibraryEntry` of `bprust-sys/ffi.h`
+struct FBPRustLibraryEntry
+{
+	uint32 AbiVersion;
+	const char* Name;
+	const char* Version;
+	uint64 DefinitionsHash;
+	int32 (*Init)(const void* Table, uint32 Version, SIZE_T Size);
+	int32 (*Shutdown)();
+	const char* (*StatusMessage)(int32 Status);
+	int32 (*PrepareReload)();
+	int32 (*Tick)();
+};
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustLibraryEntry, BPRustLibraryEntry);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, AbiVersion, BPRustLibraryEntry, abi_version);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, Name, BPRustLibraryEntry, name);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, Version, BPRustLibraryEntry, version);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, DefinitionsHash, BPRustLibraryEntry, definitions_hash);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, Init, BPRustLibraryEntry, init);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, Shutdown, BPRustLibraryEntry, shutdown);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, StatusMessage, BPRustLibraryEntry, status_message);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, PrepareReload, BPRustLibraryEntry, prepare_reload);
+BPRUST_CHECK_FFI_FIELD(FBPRustLibraryEntry, Tick, BPRustLibraryEntry, tick);
+static_assert(BPRustFFI::BPRustStatus_Ok == 0, "the plugin treats any status but 0 as a failure");
+
 void FBPRustModule::StartupModule()
 {
 	// This code will execute after your module is loaded into memory; the exact timing is specified in the .uplugin file per-module
@@ -17,26 +46,146 @@ void FBPRustModule::StartupModule()
 	// Get the base directory of this plugin
 	FString BaseDir = IPluginManager::Get().FindPlugin("BPRust")->GetBaseDir();
 
-	// Add on the relative location of the third party dll and load it
-	FString LibraryPath;
+	// Every Rust library in the platform directory is loaded, each one is initialized independently
 #if PLATFORM_WINDOWS
-	LibraryPath = FPaths::Combine(*BaseDir, TEXT("Binaries/ThirdParty/BPRustLibrary/Win64/ExampleLibrary.dll"));
+	LibraryDir = FPaths::Combine(*BaseDir, TEXT("Binaries/ThirdParty/BPRustLibrary/Win64"));
+	LibraryExtension = TEXT("dll");
 #elif PLATFORM_MAC
-    LibraryPath = FPaths::Combine(*BaseDir, TEXT("Source/ThirdParty/BPRustLibrary/Mac/Release/libExampleLibrary.dylib"));
+	LibraryDir = FPaths::Combine(*BaseDir, TEXT("Source/ThirdParty/BPRustLibrary/Mac/Release"));
+	LibraryExtension = TEXT("dylib");
 #elif PLATFORM_LINUX
-	LibraryPath = FPaths::Combine(*BaseDir, TEXT("Binaries/ThirdParty/BPRustLibrary/Linux/x86_64-unknown-linux-gnu/libExampleLibrary.so"));
+	LibraryDir = FPaths::Combine(*BaseDir, TEXT("Binaries/ThirdParty/BPRustLibrary/Linux/x86_64-unknown-linux-gnu"));
+	LibraryExtension = TEXT("so");
 #endif // PLATFORM_WINDOWS
 
-	ExampleLibraryHandle = !LibraryPath.IsEmpty() ? FPlatformProcess::GetDllHandle(*LibraryPath) : nullptr;
+	// Libraries are loaded from copies, so the originals can be rebuilt while the editor runs. Copies left by a
+	// previous session are removed first
+	ShadowDir = FPaths::Combine(FPaths::ProjectIntermediateDir(), TEXT("BPRust"));
+	IFileManager::Get().DeleteDirectory(*ShadowDir, false, true);
+
+	if (LibraryExtension)
+	{
+		LoadLibraries(LibraryDir, LibraryExtension);
+	}
+
+	ReloadCommand = IConsoleManager::Get().RegisterConsoleCommand(
+		TEXT("BPRust.Reload"), TEXT("Unload every Rust library and load it again, state persisted for hot reload is kept"),
+		FConsoleCommandDelegate::CreateRaw(this, &FBPRustModule::ReloadLibraries));
+
+	TickHandle = FTSTicker::GetCoreTicker().AddTicker(FTickerDelegate::CreateRaw(this, &FBPRustModule::Tick));
+}
+
+bool FBPRustModule::Tick(float DeltaTime)
+{
+	// Rust tasks awaiting latent actions are polled on the game thread
+	for (const FBPRustLibrary& Library : Libraries)
+	{
+		int32 Status = Library.Entry->Tick();
+		if (Status != 0)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: ticking `%s` failed with status %d: %s"), UTF8_TO_TCHAR(Library.Entry->Name), Status,
+			       UTF8_TO_TCHAR(Library.Entry->StatusMessage(Status)));
+		}
+	}
+	return true;
+}
 
-	if (ExampleLibraryHandle)
+void FBPRustModule::ReloadLibraries()
+{
+	// Let every library save its state first, the storage outlives the unloaded libraries
+	for (const FBPRustLibrary& Library : Libraries)
 	{
-		// Call the test function in the third party library that opens a message box
-		//ExampleLibraryFunction();
+		int32 Status = Library.Entry->PrepareReload();
+		if (Status != 0)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: preparing `%s` for reload failed with status %d: %s"), UTF8_TO_TCHAR(Library.Entry->Name), Status,
+			       UTF8_TO_TCHAR(Library.Entry->StatusMessage(Status)));
+		}
 	}
-	else
+
+	// Everything obtained from the old libraries is stale after this, the startup functions register it again
+	UnloadLibraries();
+
+	if (LibraryExtension)
 	{
-		FMessageDialog::Open(EAppMsgType::Ok, LOCTEXT("ThirdPartyLibraryError", "Failed to load example third party library"));
+		LoadLibraries(LibraryDir, LibraryExtension);
+	}
+}
+
+void FBPRustModule::LoadLibraries(const FString& Dir, const TCHAR* Extension)
+{
+	TArray<FString> FileNames;
+	IFileManager::Get().FindFiles(FileNames, *FPaths::Combine(Dir, FString::Printf(TEXT("*.%s"), Extension)), true, false);
+
+	for (const FString& FileName : FileNames)
+	{
+		FString LibraryPath = FPaths::Combine(Dir, FileName);
+
+		// A loaded library is locked on Windows, and reloading the same path may return the old code, so every load
+		// uses a copy with a unique name
+		FString ShadowPath = FPaths::Combine(
+			ShadowDir, FString::Printf(TEXT("%s-%u.%s"), *FPaths::GetBaseFilename(FileName), NextShadowId++, Extension));
+		if (IFileManager::Get().Copy(*ShadowPath, *LibraryPath) != COPY_OK)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: failed to copy `%s` to `%s`"), *LibraryPath, *ShadowPath);
+			continue;
+		}
+
+		void* Handle = FPlatformProcess::GetDllHandle(*ShadowPath);
+		if (!Handle)
+		{
+			FMessageDialog::Open(EAppMsgType::Ok, FText::Format(LOCTEXT("ThirdPartyLibraryError", "Failed to load Rust library {0}"), FText::FromString(LibraryPath)));
+			IFileManager::Get().Delete(*ShadowPath);
+			continue;
+		}
+
+		// The entry point is named after the file name without `lib` prefix, same as the Rust crate name
+		FString EntryName = FPaths::GetBaseFilename(FileName);
+#if !PLATFORM_WINDOWS
+		EntryName.RemoveFromStart(TEXT("lib"));
+#endif
+		EntryName = FString::Printf(TEXT("BPRustSys_%s_entry"), *EntryName);
+
+		using FEntryFunction = const FBPRustLibraryEntry* (*)();
+		FEntryFunction EntryFunction = static_cast<FEntryFunction>(FPlatformProcess::GetDllExport(Handle, *EntryName));
+
+		// Libraries without an entry point are not Rust libraries of this plugin, or were renamed after building
+		if (!EntryFunction)
+		{
+			UE_LOG(LogTemp, Warning, TEXT("BPRust: skipped `%s`, it has no entry point `%s`"), *FileName, *EntryName);
+			FPlatformProcess::FreeDllHandle(Handle);
+			IFileManager::Get().Delete(*ShadowPath);
+			continue;
+		}
+
+		const FBPRustLibraryEntry* Entry = EntryFunction();
+		if (Entry->AbiVersion != BPRustAbiVersion)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: `%s` is built for ABI version %u, but the plugin uses %u"), *FileName, Entry->AbiVersion, BPRustAbiVersion);
+			FPlatformProcess::FreeDllHandle(Handle);
+			IFileManager::Get().Delete(*ShadowPath);
+			continue;
+		}
+
+		// Installs the table and runs the startup function of the library, a library that failed is not used
+		int32 Status = Entry->Init(&GetBPRustFunctionTable(), BPRustAbiVersion, sizeof(FBPRustFunctionTable));
+		if (Status != 0)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: initializing `%s` failed with status %d: %s"), *FileName, Status, UTF8_TO_TCHAR(Entry->StatusMessage(Status)));
+			// A library that panicked in its startup function may have registered state, shut it down before unloading
+			Entry->Shutdown();
+			FPlatformProcess::FreeDllHandle(Handle);
+			IFileManager::Get().Delete(*ShadowPath);
+			continue;
+		}
+
+		UE_LOG(LogTemp, Log, TEXT("BPRust: loaded `%s` %s (definitions hash %llx)"), UTF8_TO_TCHAR(Entry->Name), UTF8_TO_TCHAR(Entry->Version), Entry->DefinitionsHash);
+
+		FBPRustLibrary Library;
+		Library.Handle = Handle;
+		Library.Entry = Entry;
+		Library.ShadowPath = ShadowPath;
+		Libraries.Add(Library);
 	}
 }
 
@@ -45,9 +194,31 @@ void FBPRustModule::ShutdownModule()
 	// This function may be called during shutdown to clean up your module.  For modules that support dynamic reloading,
 	// we call this function before unloading the module.
 
-	// Free the dll handle
-	FPlatformProcess::FreeDllHandle(ExampleLibraryHandle);
-	ExampleLibraryHandle = nullptr;
+	IConsoleManager::Get().UnregisterConsoleObject(ReloadCommand);
+	FTSTicker::GetCoreTicker().RemoveTicker(TickHandle);
+	UnloadLibraries();
+}
+
+void FBPRustModule::UnloadLibraries()
+{
+	// Unload in reverse loading order
+	for (int32 Index = Libraries.Num() - 1; Index >= 0; --Index)
+	{
+		const FBPRustLibrary& Library = Libraries[Index];
+
+		// Let the Rust library release its callbacks and GC roots before the code is unloaded
+		int32 Status = Library.Entry->Shutdown();
+		if (Status != 0)
+		{
+			UE_LOG(LogTemp, Error, TEXT("BPRust: shutting down `%s` failed with status %d: %s"), UTF8_TO_TCHAR(Library.Entry->Name), Status,
+			       UTF8_TO_TCHAR(Library.Entry->StatusMessage(Status)));
+		}
+
+		// Free the dll handle, then remove the copy it was loaded from
+		FPlatformProcess::FreeDllHandle(Library.Handle);
+		IFileManager::Get().Delete(*Library.ShadowPath);
+	}
+	Libraries.Empty();
 }
 
 #undef LOCTEXT_NAMESPACE
@@ -0,0 +1,24 @@
+// Copyright Epic Games, Inc. All Rights Reserved.
+
+#pragma once
+
+#include "CoreMinimal.h"
+#include <stddef.h>
+#include <stdint.h>
+
+// The C ABI of `bprust-sys/ffi.h`. Its opaque types are named like the engine types, so it is kept in a namespace and
+// only used to check the structs of the plugin mirroring it
+namespace BPRustFFI
+{
+#include "ffi.h"
+}
+
+// Check the size and alignment of a struct mirroring `Type` of `bprust-sys/ffi.h`
+#define BPRUST_CHECK_FFI_STRUCT(Struct, Type)                                                                          \
+	static_assert(sizeof(Struct) == sizeof(BPRustFFI::Type) && alignof(Struct) == alignof(BPRustFFI::Type),           \
+	              #Struct " diverges from `" #Type "` of bprust-sys/ffi.h")
+
+// Check the offset of a field mirroring `Type::Field` of `bprust-sys/ffi.h`
+#define BPRUST_CHECK_FFI_FIELD(Struct, StructField, Type, Field)                                                       \
+	static_assert(offsetof(Struct, StructField) == offsetof(BPRustFFI::Type, Field),                                  \
+	              #Struct "::" #StructField " diverges from `" #Type "::" #Field "` of bprust-sys/ffi.h")
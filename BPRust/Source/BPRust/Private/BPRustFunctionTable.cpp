@@ -0,0 +1,560 @@
+// Copyright Epic Games, Inc. All Rights Reserved.
+
+#include "BPRustFunctionTable.h"
+#include "BPRustFFI.h"
+#include "BPRustLatentCallbacks.h"
+#include "Engine/LatentActionManager.h"
+#include "UObject/GCObject.h"
+#include "UObject/Script.h"
+#include "UObject/UObjectGlobals.h"
+#include "Internationalization/Text.h"
+#include "Misc/ScopeLock.h"
+
+namespace
+{
+FString ToFString(FBPRustStrView View)
+{
+	FUTF8ToTCHAR Converted(View.Ptr, static_cast<int32>(View.Len));
+	return FString(Converted.Length(), Converted.Get());
+}
+
+// ---- objects ----
+
+void HandleCustomThunk(FBPRustHandler* Handler, void* UserData, void (*ResolveParam)(void* UserData, FBPRustHandler* Handler),
+                       void (*CallFunction)(void* UserData, void* Object))
+{
+	// Rust reads the parameters from the frame, which is finished like `P_FINISH` before the call
+	ResolveParam(UserData, Handler);
+	Handler->Stack->Code += !!Handler->Stack->Code;
+	CallFunction(UserData, Handler->Context);
+}
+
+EBPRustCallStatus ProcessEventWithFunction(void* Object, void* Function, void* Params)
+{
+	UObject* Target = static_cast<UObject*>(Object);
+	if (!::IsValid(Target))
+	{
+		return EBPRustCallStatus::InvalidObject;
+	}
+	if (!Function)
+	{
+		return EBPRustCallStatus::FunctionNotFound;
+	}
+	Target->ProcessEvent(static_cast<UFunction*>(Function), Params);
+	return EBPRustCallStatus::Ok;
+}
+
+EBPRustCallStatus ProcessEvent(void* Object, const char* FunctionName, void* Params)
+{
+	UObject* Target = static_cast<UObject*>(Object);
+	if (!::IsValid(Target))
+	{
+		return EBPRustCallStatus::InvalidObject;
+	}
+	return ProcessEventWithFunction(Target, Target->FindFunction(FName(UTF8_TO_TCHAR(FunctionName))), Params);
+}
+
+bool IsValidObject(void* Object)
+{
+	return ::IsValid(static_cast<UObject*>(Object));
+}
+
+void* FindClass(const char* Path)
+{
+	return LoadObject<UClass>(nullptr, UTF8_TO_TCHAR(Path), nullptr, LOAD_NoWarn | LOAD_Quiet);
+}
+
+bool ObjectIsA(void* Object, void* Class)
+{
+	UObject* Target = static_cast<UObject*>(Object);
+	return ::IsValid(Target) && Class && Target->IsA(static_cast<UClass*>(Class));
+}
+
+void* ObjectGetClass(void* Object)
+{
+	UObject* Target = static_cast<UObject*>(Object);
+	return ::IsValid(Target) ? Target->GetClass() : nullptr;
+}
+
+void* ClassDefaultObject(void* Class)
+{
+	return static_cast<UClass*>(Class)->GetDefaultObject();
+}
+
+void* FindFunction(void* Class, const char* FunctionName)
+{
+	return static_cast<UClass*>(Class)->FindFunctionByName(FName(UTF8_TO_TCHAR(FunctionName)));
+}
+
+// Call `Visit` for the `CPF_Parm` properties of `Function` at the ascending `Indices`, counted in declaration order
+void ForEachParam(void* Function, const SIZE_T* Indices, SIZE_T Len, TFunctionRef<void(FProperty*)> Visit)
+{
+	SIZE_T ParamIndex = 0;
+	SIZE_T Next = 0;
+	for (TFieldIterator<FProperty> It(static_cast<UFunction*>(Function)); It && Next < Len; ++It)
+	{
+		if (!It->HasAnyPropertyFlags(CPF_Parm))
+		{
+			continue;
+		}
+		if (ParamIndex == Indices[Next])
+		{
+			Visit(*It);
+			++Next;
+		}
+		++ParamIndex;
+	}
+}
+
+void InitParams(void* Function, void* Params, const SIZE_T* Indices, SIZE_T Len)
+{
+	ForEachParam(Function, Indices, Len, [Params](FProperty* Property) { Property->InitializeValue_InContainer(Params); });
+}
+
+void DestroyParams(void* Function, void* Params, const SIZE_T* Indices, SIZE_T Len)
+{
+	ForEachParam(Function, Indices, Len, [Params](FProperty* Property) { Property->DestroyValue_InContainer(Params); });
+}
+
+void NameFromString(FName* Out, FBPRustStrView Name)
+{
+	new (Out) FName(*ToFString(Name), FNAME_Add);
+}
+
+// ---- GC roots ----
+
+class FBPRustGCRoots : public FGCObject
+{
+public:
+	uint64 Add(UObject* Object)
+	{
+		FScopeLock Lock(&CriticalSection);
+		uint64 Handle = NextHandle++;
+		Roots.Add(Handle, Object);
+		return Handle;
+	}
+
+	UObject* Get(uint64 Handle)
+	{
+		FScopeLock Lock(&CriticalSection);
+		TObjectPtr<UObject>* Object = Roots.Find(Handle);
+		return Object && ::IsValid(*Object) ? Object->Get() : nullptr;
+	}
+
+	void Remove(uint64 Handle)
+	{
+		FScopeLock Lock(&CriticalSection);
+		Roots.Remove(Handle);
+	}
+
+	virtual void AddReferencedObjects(FReferenceCollector& Collector) override
+	{
+		FScopeLock Lock(&CriticalSection);
+		for (TPair<uint64, TObjectPtr<UObject>>& Root : Roots)
+		{
+			Collector.AddReferencedObject(Root.Value);
+		}
+	}
+
+	virtual FString GetReferencerName() const override
+	{
+		return TEXT("BPRust GC roots");
+	}
+
+private:
+	FCriticalSection CriticalSection;
+	TMap<uint64, TObjectPtr<UObject>> Roots;
+	// Handles are never reused, Rust may still hold handles of removed roots
+	uint64 NextHandle = 1;
+};
+
+FBPRustGCRoots& GetGCRoots()
+{
+	// Never destroyed, libraries may release their roots while the engine shuts down
+	static FBPRustGCRoots* Roots = new FBPRustGCRoots();
+	return *Roots;
+}
+
+uint64 GCRootAdd(void* Object)
+{
+	return GetGCRoots().Add(static_cast<UObject*>(Object));
+}
+
+void* GCRootGet(uint64 Handle)
+{
+	return GetGCRoots().Get(Handle);
+}
+
+void GCRootRemove(uint64 Handle)
+{
+	GetGCRoots().Remove(Handle);
+}
+
+// ---- text ----
+
+void TextFromString(FText* Out, FBPRustStrView String)
+{
+	new (Out) FText(FText::FromString(ToFString(String)));
+}
+
+void TextAsCultureInvariant(FText* Out, FBPRustStrView String)
+{
+	new (Out) FText(FText::AsCultureInvariant(ToFString(String)));
+}
+
+bool TextFind(FText* Out, FBPRustStrView Namespace, FBPRustStrView Key)
+{
+	FText Found;
+	if (!FText::FindText(ToFString(Namespace), ToFString(Key), Found))
+	{
+		return false;
+	}
+	new (Out) FText(MoveTemp(Found));
+	return true;
+}
+
+void TextFormat(FText* Out, const FText* Pattern, const FBPRustFormatNamedArgument* Args, SIZE_T ArgsLen)
+{
+	FFormatNamedArguments Arguments;
+	for (SIZE_T Index = 0; Index < ArgsLen; ++Index)
+	{
+		const FBPRustFormatArgumentValue& Value = Args[Index].Value;
+		FString Name = ToFString(Args[Index].Name);
+		switch (Value.Tag)
+		{
+		case EBPRustFormatArgumentTag::Int:
+			Arguments.Add(Name, Value.IntValue);
+			break;
+		case EBPRustFormatArgumentTag::UInt:
+			Arguments.Add(Name, Value.UIntValue);
+			break;
+		case EBPRustFormatArgumentTag::Float:
+			Arguments.Add(Name, Value.FloatValue);
+			break;
+		case EBPRustFormatArgumentTag::Double:
+			Arguments.Add(Name, Value.DoubleValue);
+			break;
+		case EBPRustFormatArgumentTag::Text:
+			Arguments.Add(Name, *Value.TextValue);
+			break;
+		}
+	}
+	new (Out) FText(FText::Format(FTextFormat(*Pattern), Arguments));
+}
+
+void TextToString(const FText* Text, void* UserData, void (*Write)(void* UserData, FBPRustStrView Chars))
+{
+	FTCHARToUTF8 Converted(*Text->ToString());
+	Write(UserData, {Converted.Get(), static_cast<SIZE_T>(Converted.Length())});
+}
+
+void TextClone(FText* Out, const FText* Text)
+{
+	new (Out) FText(*Text);
+}
+
+void TextDrop(FText* Text)
+{
+	Text->~FText();
+}
+
+// ---- latent actions ----
+
+struct FBPRustPendingLatentAction
+{
+	uint64 Id;
+	void (*OnComplete)(uint64 Id);
+};
+
+// Latent actions run on the game thread, so these are only accessed there. The linkage identifies the action across
+// every library, the ids of the libraries may be equal
+TMap<int32, FBPRustPendingLatentAction>& GetPendingLatentActions()
+{
+	static TMap<int32, FBPRustPendingLatentAction> PendingActions;
+	return PendingActions;
+}
+
+UBPRustLatentCallbacks* GetLatentCallbacks()
+{
+	// Rooted, it must outlive every action that may still call it
+	static UBPRustLatentCallbacks* Callbacks = []
+	{
+		UBPRustLatentCallbacks* Object = NewObject<UBPRustLatentCallbacks>(GetTransientPackage());
+		Object->AddToRoot();
+		return Object;
+	}();
+	return Callbacks;
+}
+
+void LatentActionInfo(void* Out, uint64 Id, void (*OnComplete)(uint64 Id))
+{
+	static int32 NextLinkage = 0;
+	int32 Linkage = NextLinkage++;
+	GetPendingLatentActions().Add(Linkage, {Id, OnComplete});
+
+	// Latent actions are keyed by callback target and UUID, so the linkage doubles as the UUID
+	new (Out) FLatentActionInfo(Linkage, Linkage, TEXT("OnLatentActionComplete"), GetLatentCallbacks());
+}
+
+void LatentActionForget(uint64 Id, void (*OnComplete)(uint64 Id))
+{
+	for (auto It = GetPendingLatentActions().CreateIterator(); It; ++It)
+	{
+		if (It->Value.Id == Id && It->Value.OnComplete == OnComplete)
+		{
+			It.RemoveCurrent();
+			return;
+		}
+	}
+}
+
+// ---- values moved out of parameter frames ----
+
+void StringDrop(FString* String)
+{
+	String->~FString();
+}
+
+// The untyped containers only free their allocations, Rust can't tell the destructors of the elements
+
+void ScriptArrayDrop(FScriptArray* Array)
+{
+	Array->~FScriptArray();
+}
+
+void ScriptSetDrop(FScriptSet* Set)
+{
+	Set->~FScriptSet();
+}
+
+void ScriptMapDrop(FScriptMap* Map)
+{
+	Map->~FScriptMap();
+}
+
+// ---- capabilities ----
+
+enum class EBPRustLogVerbosity : uint8
+{
+	Fatal = 1,
+	Error = 2,
+	Warning = 3,
+	Display = 4,
+	Log = 5,
+	Verbose = 6,
+	VeryVerbose = 7,
+};
+
+// "BPRust.Log.v1"
+struct FBPRustLogCapability
+{
+	void (*Log)(EBPRustLogVerbosity Verbosity, FBPRustStrView Message);
+};
+
+void Log(EBPRustLogVerbosity Verbosity, FBPRustStrView View)
+{
+	FString Message = ToFString(View);
+	switch (Verbosity)
+	{
+	case EBPRustLogVerbosity::Fatal:
+		UE_LOG(LogTemp, Fatal, TEXT("%s"), *Message);
+		break;
+	case EBPRustLogVerbosity::Error:
+		UE_LOG(LogTemp, Error, TEXT("%s"), *Message);
+		break;
+	case EBPRustLogVerbosity::Warning:
+		UE_LOG(LogTemp, Warning, TEXT("%s"), *Message);
+		break;
+	case EBPRustLogVerbosity::Display:
+		UE_LOG(LogTemp, Display, TEXT("%s"), *Message);
+		break;
+	case EBPRustLogVerbosity::Verbose:
+		UE_LOG(LogTemp, Verbose, TEXT("%s"), *Message);
+		break;
+	case EBPRustLogVerbosity::VeryVerbose:
+		UE_LOG(LogTemp, VeryVerbose, TEXT("%s"), *Message);
+		break;
+	default:
+		UE_LOG(LogTemp, Log, TEXT("%s"), *Message);
+		break;
+	}
+}
+
+const FBPRustLogCapability LogCapability = {&Log};
+
+// "BPRust.HotReload.v1"
+struct FBPRustHotReloadCapability
+{
+	void (*Store)(FBPRustStrView Key, const uint8* Data, SIZE_T Len);
+	bool (*Take)(FBPRustStrView Key, void* UserData, void (*Write)(void* UserData, const uint8* Data, SIZE_T Len));
+};
+
+// Owned by the plugin, so the state outlives the libraries unloaded for a reload
+TMap<FString, TArray<uint8>>& GetReloadStorage()
+{
+	static TMap<FString, TArray<uint8>> Storage;
+	return Storage;
+}
+
+void HotReloadStore(FBPRustStrView Key, const uint8* Data, SIZE_T Len)
+{
+	GetReloadStorage().Add(ToFString(Key), TArray<uint8>(Data, static_cast<int32>(Len)));
+}
+
+bool HotReloadTake(FBPRustStrView Key, void* UserData, void (*Write)(void* UserData, const uint8* Data, SIZE_T Len))
+{
+	TArray<uint8> Data;
+	if (!GetReloadStorage().RemoveAndCopyValue(ToFString(Key), Data))
+	{
+		return false;
+	}
+	Write(UserData, Data.GetData(), static_cast<SIZE_T>(Data.Num()));
+	return true;
+}
+
+const FBPRustHotReloadCapability HotReloadCapability = {&HotReloadStore, &HotReloadTake};
+
+const void* GetCapability(const char* Name)
+{
+	if (FCStringAnsi::Strcmp(Name, "BPRust.Log.v1") == 0)
+	{
+		return &LogCapability;
+	}
+	if (FCStringAnsi::Strcmp(Name, "BPRust.HotReload.v1") == 0)
+	{
+		return &HotReloadCapability;
+	}
+	return nullptr;
+}
+
+const FBPRustFunctionTable FunctionTable = {
+	&HandleCustomThunk,
+	&ProcessEvent,
+	&IsValidObject,
+	&FindClass,
+	&ObjectIsA,
+
+	&GCRootAdd,
+	&GCRootGet,
+	&GCRootRemove,
+
+	&TextFromString,
+	&TextAsCultureInvariant,
+	&TextFind,
+	&TextFormat,
+	&TextToString,
+	&TextClone,
+	&TextDrop,
+
+	&GetCapability,
+	&ObjectGetClass,
+	&FindFunction,
+	&ProcessEventWithFunction,
+	&InitParams,
+	&DestroyParams,
+	&NameFromString,
+	&LatentActionInfo,
+	&LatentActionForget,
+
+	&StringDrop,
+	&ScriptArrayDrop,
+	&ScriptSetDrop,
+	&ScriptMapDrop,
+
+	&ClassDefaultObject,
+};
+
+// ---- checked against bprust-sys/ffi.h ----
+
+static_assert(BPRustAbiVersion == BPRUST_ABI_VERSION, "BPRustAbiVersion diverges from BPRUST_ABI_VERSION of bprust-sys/ffi.h");
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustStrView, StrView);
+BPRUST_CHECK_FFI_FIELD(FBPRustStrView, Ptr, StrView, ptr);
+BPRUST_CHECK_FFI_FIELD(FBPRustStrView, Len, StrView, len);
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustHandler, Handler);
+BPRUST_CHECK_FFI_FIELD(FBPRustHandler, Context, Handler, context);
+BPRUST_CHECK_FFI_FIELD(FBPRustHandler, Stack, Handler, fframe);
+BPRUST_CHECK_FFI_FIELD(FBPRustHandler, Result, Handler, z_param_result);
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustFormatArgumentValue, FormatArgumentValue);
+BPRUST_CHECK_FFI_FIELD(FBPRustFormatArgumentValue, Tag, FormatArgumentValue, tag);
+BPRUST_CHECK_FFI_FIELD(FBPRustFormatArgumentValue, IntValue, FormatArgumentValue, int_value);
+static_assert(static_cast<uint32>(EBPRustFormatArgumentTag::Int) == BPRustFFI::FormatArgumentValue_Int &&
+                  static_cast<uint32>(EBPRustFormatArgumentTag::UInt) == BPRustFFI::FormatArgumentValue_UInt &&
+                  static_cast<uint32>(EBPRustFormatArgumentTag::Float) == BPRustFFI::FormatArgumentValue_Float &&
+                  static_cast<uint32>(EBPRustFormatArgumentTag::Double) == BPRustFFI::FormatArgumentValue_Double &&
+                  static_cast<uint32>(EBPRustFormatArgumentTag::Text) == BPRustFFI::FormatArgumentValue_Text,
+              "EBPRustFormatArgumentTag diverges from `FormatArgumentValueTag` of bprust-sys/ffi.h");
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustFormatNamedArgument, FormatNamedArgument);
+BPRUST_CHECK_FFI_FIELD(FBPRustFormatNamedArgument, Name, FormatNamedArgument, name);
+BPRUST_CHECK_FFI_FIELD(FBPRustFormatNamedArgument, Value, FormatNamedArgument, value);
+
+static_assert(static_cast<int32>(EBPRustCallStatus::Ok) == BPRustFFI::BPRustCallStatus_Ok &&
+                  static_cast<int32>(EBPRustCallStatus::InvalidObject) == BPRustFFI::BPRustCallStatus_InvalidObject &&
+                  static_cast<int32>(EBPRustCallStatus::FunctionNotFound) == BPRustFFI::BPRustCallStatus_FunctionNotFound,
+              "EBPRustCallStatus diverges from `BPRustCallStatus` of bprust-sys/ffi.h");
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustFunctionTable, CppFunctionTable);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, HandleCustomThunk, CppFunctionTable, handle_custom_thunk);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ProcessEvent, CppFunctionTable, process_event);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, IsValid, CppFunctionTable, is_valid);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, FindClass, CppFunctionTable, find_class);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ObjectIsA, CppFunctionTable, object_is_a);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, GCRootAdd, CppFunctionTable, gc_root_add);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, GCRootGet, CppFunctionTable, gc_root_get);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, GCRootRemove, CppFunctionTable, gc_root_remove);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextFromString, CppFunctionTable, text_from_string);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextAsCultureInvariant, CppFunctionTable, text_as_culture_invariant);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextFind, CppFunctionTable, text_find);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextFormat, CppFunctionTable, text_format);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextToString, CppFunctionTable, text_to_string);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextClone, CppFunctionTable, text_clone);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, TextDrop, CppFunctionTable, text_drop);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, GetCapability, CppFunctionTable, get_capability);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ObjectGetClass, CppFunctionTable, object_get_class);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, FindFunction, CppFunctionTable, find_function);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ProcessEventWithFunction, CppFunctionTable, process_event_with_function);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, InitParams, CppFunctionTable, init_params);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, DestroyParams, CppFunctionTable, destroy_params);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, NameFromString, CppFunctionTable, name_from_string);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, LatentActionInfo, CppFunctionTable, latent_action_info);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, LatentActionForget, CppFunctionTable, latent_action_forget);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, StringDrop, CppFunctionTable, string_drop);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ScriptArrayDrop, CppFunctionTable, script_array_drop);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ScriptSetDrop, CppFunctionTable, script_set_drop);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ScriptMapDrop, CppFunctionTable, script_map_drop);
+BPRUST_CHECK_FFI_FIELD(FBPRustFunctionTable, ClassDefaultObject, CppFunctionTable, class_default_object);
+
+static_assert(static_cast<uint8>(EBPRustLogVerbosity::Fatal) == BPRustFFI::LogVerbosity_Fatal &&
+                  static_cast<uint8>(EBPRustLogVerbosity::Error) == BPRustFFI::LogVerbosity_Error &&
+                  static_cast<uint8>(EBPRustLogVerbosity::Warning) == BPRustFFI::LogVerbosity_Warning &&
+                  static_cast<uint8>(EBPRustLogVerbosity::Display) == BPRustFFI::LogVerbosity_Display &&
+                  static_cast<uint8>(EBPRustLogVerbosity::Log) == BPRustFFI::LogVerbosity_Log &&
+                  static_cast<uint8>(EBPRustLogVerbosity::Verbose) == BPRustFFI::LogVerbosity_Verbose &&
+                  static_cast<uint8>(EBPRustLogVerbosity::VeryVerbose) == BPRustFFI::LogVerbosity_VeryVerbose,
+              "EBPRustLogVerbosity diverges from `LogVerbosity` of bprust-sys/ffi.h");
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustLogCapability, LogCapability);
+BPRUST_CHECK_FFI_FIELD(FBPRustLogCapability, Log, LogCapability, log);
+
+BPRUST_CHECK_FFI_STRUCT(FBPRustHotReloadCapability, HotReloadCapability);
+BPRUST_CHECK_FFI_FIELD(FBPRustHotReloadCapability, Store, HotReloadCapability, store);
+BPRUST_CHECK_FFI_FIELD(FBPRustHotReloadCapability, Take, HotReloadCapability, take);
+} // namespace
+
+const FBPRustFunctionTable& GetBPRustFunctionTable()
+{
+	return FunctionTable;
+}
+
+void UBPRustLatentCallbacks::OnLatentActionComplete(int32 Linkage)
+{
+	FBPRustPendingLatentAction Action;
+	// Forgotten actions, e.g. of an unloaded library, are ignored
+	if (GetPendingLatentActions().RemoveAndCopyValue(Linkage, Action))
+	{
+		Action.OnComplete(Action.Id);
+	}
+}
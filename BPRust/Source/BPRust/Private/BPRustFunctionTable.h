@@ -0,0 +1,100 @@
+// Copyright Epic Games, Inc. All Rights Reserved.
+
+#pragma once
+
+#include "CoreMinimal.h"
+
+// Mirrors `BPRUST_ABI_VERSION` and `CppFunctionTable` of `bprust-sys/ffi.h`, the plugin passes this table to every
+// Rust library it loads
+static constexpr uint32 BPRustAbiVersion = 3;
+
+// Returned by `ProcessEvent`, mirrors `BPRustCallStatus`
+enum class EBPRustCallStatus : int32
+{
+	Ok = 0,
+	InvalidObject = 1,
+	FunctionNotFound = 2,
+};
+
+struct FBPRustStrView
+{
+	const char* Ptr;
+	SIZE_T Len;
+};
+
+struct FBPRustHandler
+{
+	UObject* Context;
+	FFrame* Stack;
+	void* Result;
+};
+
+enum class EBPRustFormatArgumentTag : uint32
+{
+	Int,
+	UInt,
+	Float,
+	Double,
+	Text,
+};
+
+struct FBPRustFormatArgumentValue
+{
+	EBPRustFormatArgumentTag Tag;
+	union
+	{
+		int64 IntValue;
+		uint64 UIntValue;
+		float FloatValue;
+		double DoubleValue;
+		const FText* TextValue;
+	};
+};
+
+struct FBPRustFormatNamedArgument
+{
+	FBPRustStrView Name;
+	FBPRustFormatArgumentValue Value;
+};
+
+struct FBPRustFunctionTable
+{
+	void (*HandleCustomThunk)(FBPRustHandler* Handler, void* UserData, void (*ResolveParam)(void* UserData, FBPRustHandler* Handler),
+	                          void (*CallFunction)(void* UserData, void* Object));
+	EBPRustCallStatus (*ProcessEvent)(void* Object, const char* FunctionName, void* Params);
+	bool (*IsValid)(void* Object);
+	void* (*FindClass)(const char* Path);
+	bool (*ObjectIsA)(void* Object, void* Class);
+
+	uint64 (*GCRootAdd)(void* Object);
+	void* (*GCRootGet)(uint64 Handle);
+	void (*GCRootRemove)(uint64 Handle);
+
+	void (*TextFromString)(FText* Out, FBPRustStrView String);
+	void (*TextAsCultureInvariant)(FText* Out, FBPRustStrView String);
+	bool (*TextFind)(FText* Out, FBPRustStrView Namespace, FBPRustStrView Key);
+	void (*TextFormat)(FText* Out, const FText* Pattern, const FBPRustFormatNamedArgument* Args, SIZE_T ArgsLen);
+	void (*TextToString)(const FText* Text, void* UserData, void (*Write)(void* UserData, FBPRustStrView Chars));
+	void (*TextClone)(FText* Out, const FText* Text);
+	void (*TextDrop)(FText* Text);
+
+	// ---- optional entries ----
+
+	const void* (*GetCapability)(const char* Name);
+	void* (*ObjectGetClass)(void* Object);
+	void* (*FindFunction)(void* Class, const char* FunctionName);
+	EBPRustCallStatus (*ProcessEventWithFunction)(void* Object, void* Function, void* Params);
+	void (*InitParams)(void* Function, void* Params, const SIZE_T* Indices, SIZE_T Len);
+	void (*DestroyParams)(void* Function, void* Params, const SIZE_T* Indices, SIZE_T Len);
+	void (*NameFromString)(FName* Out, FBPRustStrView Name);
+	void (*LatentActionInfo)(void* Out, uint64 Id, void (*OnComplete)(uint64 Id));
+	void (*LatentActionForget)(uint64 Id, void (*OnComplete)(uint64 Id));
+	void (*StringDrop)(FString* String);
+	void (*ScriptArrayDrop)(FScriptArray* Array);
+	void (*ScriptSetDrop)(FScriptSet* Set);
+	void (*ScriptMapDrop)(FScriptMap* Map);
+	void* (*ClassDefaultObject)(void* Class);
+};
+
+/** The table passed to `BPRustLibraryEntry::init`, entries the plugin doesn't provide are null */
+const FBPRustFunctionTable& GetBPRustFunctionTable();
@@ -0,0 +1,19 @@
+// Copyright Epic Games, Inc. All Rights Reserved.
+
+#pragma once
+
+#include "CoreMinimal.h"
+#include "UObject/Object.h"
+#include "BPRustLatentCallbacks.generated.h"
+
+/** The callback target of the latent actions Rust starts, completes the Rust call of each action */
+UCLASS(Transient)
+class UBPRustLatentCallbacks : public UObject
+{
+	GENERATED_BODY()
+
+public:
+	/** Called by the latent action manager once the action with the `Linkage` of its `FLatentActionInfo` finishes */
+	UFUNCTION()
+	void OnLatentActionComplete(int32 Linkage);
+};
@@ -3,6 +3,7 @@
 #pragma once
 
 #include "Modules/ModuleManager.h"
+#include "Containers/Ticker.h"
 
 class FBPRustModule : public IModuleInterface
 {
@@ -12,7 +13,44 @@ public:
 	virtual void StartupModule() override;
 	virtual void ShutdownModule() override;
 
+	/** Unload every Rust library and load them again, Rust state persisted for hot reload is kept. Run by the console
+	 * command `BPRust.Reload` */
+	void ReloadLibraries();
+
 private:
-	/** Handle to the test dll we will load */
-	void*	ExampleLibraryHandle;
+	/** A loaded Rust library, found by its entry point `BPRustSys_<FileName>_entry` */
+	struct FBPRustLibrary
+	{
+		void* Handle = nullptr;
+		/** `BPRustLibraryEntry` of `bprust-sys/ffi.h` */
+		const struct FBPRustLibraryEntry* Entry = nullptr;
+		/** The copy of the library file that is loaded */
+		FString ShadowPath;
+	};
+
+	/** Load every Rust library found in `Dir` */
+	void LoadLibraries(const FString& Dir, const TCHAR* Extension);
+
+	/** Shut down and unload every Rust library in reverse loading order */
+	void UnloadLibraries();
+
+	/** Poll the Rust tasks of every library */
+	bool Tick(float DeltaTime);
+
+	FTSTicker::FDelegateHandle TickHandle;
+
+	/** `BPRust.Reload` */
+	class IConsoleObject* ReloadCommand = nullptr;
+
+	/** Directory of the copies the libraries are loaded from, and the id of the next copy */
+	FString ShadowDir;
+	uint32 NextShadowId = 0;
+
+	/** Directory and file extension of the Rust libraries, kept for reloading */
+	FString LibraryDir;
+	const TCHAR* LibraryExtension = nullptr;
+
+	/** Initialized Rust libraries in loading order, a library whose `Init` failed is unloaded right away, so only
+	 * these are ticked, reloaded and shut down */
+	TArray<FBPRustLibrary> Libraries;
 };
@@ -93,31 +93,50 @@ bool WriteProperty(FProperty* Property, FString& OutStr)
 	FString Name = Property->GetAuthoredName(); //Property->GetName();
 	// const TCHAR* ParamType = RecognizeParamType(Property->PropertyFlags);
 
+	// Default pin value of a function parameter
+	FString ExtraDefaultJson;
+	UFunction* Func = Property->GetOwner<UFunction>();
+	FString DefaultKey = FString::Printf(TEXT("CPP_Default_%s"), *Property->GetName());
+	if (Func && Func->HasMetaData(*DefaultKey))
+	{
+		ExtraDefaultJson = FString::Printf(TEXT(",\n\"default\": \"%s\""), *Func->GetMetaData(*DefaultKey).ReplaceCharWithEscapedChar());
+	}
+
 	const TCHAR* Format = TEXT(
 		R"JSON({
 "name": "{0}",
 "property": "{1}",
 "type_info": "{2}",
-"flags": {3}
+"flags": {3},
+"offset": {4},
+"size": {5}{6}
 })JSON"
 	);
 	OutStr = FString::Format(Format, {
 		                         Name,
 		                         PropertyType,
 		                         RelativeType,
-		                         Property->PropertyFlags
+		                         Property->PropertyFlags,
+		                         Property->GetOffset_ForInternal(),
+		                         Property->GetSize(),
+		                         ExtraDefaultJson
 	                         }
 	);
 	return true;
 }
 
 template<class Iterator>
-bool WritePropertiesArray(Iterator PropIt, FString &OutputJson, bool IgnoreUnsupported = false)
+bool WritePropertiesArray(Iterator PropIt, FString &OutputJson, bool IgnoreUnsupported = false, EPropertyFlags RequiredFlags = CPF_None)
 {
 	OutputJson = TEXT("[");
 	bool ShouldAppendComma = false;
 	for (;PropIt;++PropIt)
 	{
+		if (!PropIt->HasAllPropertyFlags(RequiredFlags))
+		{
+			continue;
+		}
+
 		FString MemberJson;
 		if (!WriteProperty(*PropIt, MemberJson))
 		{
@@ -146,7 +165,8 @@ FString WriteFunctions(UClass* Class)
     {
         UFunction* Func = *FuncIt;
     	FString Params;
-    	if (!WritePropertiesArray(TFieldIterator<FProperty>(Func), Params))
+    	// Blueprint functions also own their local variables as properties, only export the parameters
+    	if (!WritePropertiesArray(TFieldIterator<FProperty>(Func), Params, false, CPF_Parm))
     	{
     		continue;
     	}
@@ -157,15 +177,32 @@ FString WriteFunctions(UClass* Class)
     	bool IsRustOverride = Category == TEXT("RustOverride") || Category.StartsWith(TEXT("RustOverride|"));
     	const TCHAR* ExtraOverrideJson = IsRustOverride ? TEXT("\"override\": true,\n") : TEXT("");
 
+    	// Name of the parameter Blueprint fills in with the world context
+    	FString ExtraWorldContextJson;
+    	if (Func->HasMetaData(TEXT("WorldContext")))
+    	{
+    		ExtraWorldContextJson = FString::Printf(TEXT("\"world_context\": \"%s\",\n"), *Func->GetMetaData(TEXT("WorldContext")));
+    	}
+
+    	// Name of the `FLatentActionInfo` parameter of a latent function
+    	FString ExtraLatentInfoJson;
+    	if (Func->HasMetaData(TEXT("Latent")) && Func->HasMetaData(TEXT("LatentInfo")))
+    	{
+    		ExtraLatentInfoJson = FString::Printf(TEXT("\"latent_info\": \"%s\",\n"), *Func->GetMetaData(TEXT("LatentInfo")));
+    	}
+
     	AppendComma(OutJson,FunctionListAppendComma);
     	const TCHAR* Format = TEXT(
 		R"({
 "name": "{0}",
-"id": "{1}",{2}
-"params": {3}
+"id": "{1}",{2}{3}{4}
+"flags": {5},
+"parms_size": {6},
+"params": {7}
 })"
 		);
-    	OutJson += FString::Format(Format, {Name, Id, ExtraOverrideJson, Params});
+    	OutJson += FString::Format(Format, {Name, Id, ExtraOverrideJson, ExtraWorldContextJson, ExtraLatentInfoJson, static_cast<uint32>(Func->FunctionFlags),
+    	                                    static_cast<int32>(Func->ParmsSize), Params});
     }
 
 	OutJson += TEXT("]");
@@ -177,11 +214,16 @@ FString WriteClass(UClass* const Class)
 	FString ClassName = Class->GetAuthoredName();
     FString Id = Class->GetName();
 
+	FString Package = Class->GetPackage()->GetName();
+	FString Path = Class->GetPathName();
+
 	FString SuperClassName;
+	FString SuperClassId;
 	UClass *SuperClass = Class->GetSuperClass(); 
 	if (IsValid(SuperClass))
 	{
 		SuperClassName = SuperClass->GetAuthoredName();
+		SuperClassId = SuperClass->GetName();
 	}
 	
 	FString Functions = WriteFunctions(Class);
@@ -193,12 +235,15 @@ FString WriteClass(UClass* const Class)
 		R"({
 "name": "{0}",
 "id": "{1}",
-"super": "{2}",
-"properties": {3},
-"functions": {4}
+"package": "{2}",
+"path": "{3}",
+"super": "{4}",
+"super_id": "{5}",
+"properties": {6},
+"functions": {7}
 })"
 	);
-	return FString::Format(Format, {ClassName, Id, SuperClassName, Properties, Functions});
+	return FString::Format(Format, {ClassName, Id, Package, Path, SuperClassName, SuperClassId, Properties, Functions});
 }
 
 bool WriteStruct(UScriptStruct* const Struct, FString &OutStr)
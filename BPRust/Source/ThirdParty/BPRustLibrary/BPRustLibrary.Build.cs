@@ -10,28 +10,19 @@ public class BPRustLibrary : ModuleRules
 		Type = ModuleType.External;
 		PublicSystemIncludePaths.Add("$(ModuleDir)/Public");
 
+		// The Rust libraries are not linked, the plugin loads every library in the platform directory at runtime
+		// and finds it by its entry point. Only make sure they are staged along with the executable.
 		if (Target.Platform == UnrealTargetPlatform.Win64)
 		{
-			// Add the import library
-			PublicAdditionalLibraries.Add(Path.Combine(ModuleDirectory, "x64", "Release", "ExampleLibrary.lib"));
-
-			// Delay-load the DLL, so we can load it from the right place first
-			PublicDelayLoadDLLs.Add("ExampleLibrary.dll");
-
-			// Ensure that the DLL is staged along with the executable
-			RuntimeDependencies.Add("$(PluginDir)/Binaries/ThirdParty/BPRustLibrary/Win64/ExampleLibrary.dll");
+			RuntimeDependencies.Add("$(PluginDir)/Binaries/ThirdParty/BPRustLibrary/Win64/*.dll");
 		}
 		else if (Target.Platform == UnrealTargetPlatform.Mac)
 		{
-			PublicDelayLoadDLLs.Add(Path.Combine(ModuleDirectory, "Mac", "Release", "libExampleLibrary.dylib"));
-			RuntimeDependencies.Add("$(PluginDir)/Source/ThirdParty/BPRustLibrary/Mac/Release/libExampleLibrary.dylib");
+			RuntimeDependencies.Add("$(PluginDir)/Source/ThirdParty/BPRustLibrary/Mac/Release/*.dylib");
 		}
 		else if (Target.Platform == UnrealTargetPlatform.Linux)
 		{
-			string ExampleSoPath = Path.Combine("$(PluginDir)", "Binaries", "ThirdParty", "BPRustLibrary", "Linux", "x86_64-unknown-linux-gnu", "libExampleLibrary.so");
-			PublicAdditionalLibraries.Add(ExampleSoPath);
-			PublicDelayLoadDLLs.Add(ExampleSoPath);
-			RuntimeDependencies.Add(ExampleSoPath);
+			RuntimeDependencies.Add(Path.Combine("$(PluginDir)", "Binaries", "ThirdParty", "BPRustLibrary", "Linux", "x86_64-unknown-linux-gnu", "*.so"));
 		}
 	}
 }
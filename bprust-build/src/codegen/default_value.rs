@@ -0,0 +1,102 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+
+use crate::{PropPrimitiveType, PropertyType};
+
+/// Parses the `CPP_Default_*` metadata of a function parameter into a Rust expression, `None` if
+/// the value or its type is not supported.
+///
+/// Struct and enum defaults are not supported until struct and enum types are generated.
+pub(super) fn parse(prop_type: &PropertyType, nullable: bool, value: &str) -> Option<TokenStream> {
+    let value = value.trim();
+    match *prop_type {
+        PropertyType::Primitive(PropPrimitiveType::Name) => {
+            Some(if value.is_empty() || value == "None" {
+                quote! { bprust_sys::FName::NONE }
+            } else {
+                quote! { bprust_sys::FName::new(#value) }
+            })
+        }
+        PropertyType::Primitive(prim) => parse_primitive(prim, value),
+        PropertyType::Object(_) => (nullable && (value.is_empty() || value == "None"))
+            .then(|| quote! { ::core::option::Option::None }),
+        PropertyType::Enum(_) | PropertyType::Struct(_) => None,
+    }
+}
+
+fn parse_primitive(prim: PropPrimitiveType, value: &str) -> Option<TokenStream> {
+    let literal = match prim {
+        PropPrimitiveType::Bool => {
+            return if value.eq_ignore_ascii_case("true") {
+                Some(quote! { true })
+            } else if value.eq_ignore_ascii_case("false") {
+                Some(quote! { false })
+            } else {
+                None
+            };
+        }
+        PropPrimitiveType::Byte => Literal::u8_suffixed(value.parse().ok()?),
+        PropPrimitiveType::Int => Literal::i32_suffixed(value.parse().ok()?),
+        PropPrimitiveType::Int64 => Literal::i64_suffixed(value.parse().ok()?),
+        PropPrimitiveType::Float => {
+            Literal::f32_suffixed(value.parse().ok().filter(|v: &f32| v.is_finite())?)
+        }
+        PropPrimitiveType::Double => {
+            Literal::f64_suffixed(value.parse().ok().filter(|v: &f64| v.is_finite())?)
+        }
+        // no constructor for these yet
+        PropPrimitiveType::Name | PropPrimitiveType::Str | PropPrimitiveType::Text => return None,
+    };
+    Some(quote! { #literal })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitives() {
+        let parse = |prim, value| parse_primitive(prim, value).map(|tokens| tokens.to_string());
+        assert_eq!(parse(PropPrimitiveType::Bool, "True"), Some("true".into()));
+        assert_eq!(
+            parse(PropPrimitiveType::Bool, "false"),
+            Some("false".into())
+        );
+        assert_eq!(parse(PropPrimitiveType::Bool, "1"), None);
+        assert_eq!(parse(PropPrimitiveType::Byte, "255"), Some("255u8".into()));
+        assert_eq!(parse(PropPrimitiveType::Byte, "256"), None);
+        assert_eq!(parse(PropPrimitiveType::Int, "-3"), Some("- 3i32".into()));
+        assert_eq!(parse(PropPrimitiveType::Int64, "42"), Some("42i64".into()));
+        assert_eq!(
+            parse(PropPrimitiveType::Float, "0.5"),
+            Some("0.5f32".into())
+        );
+        assert_eq!(parse(PropPrimitiveType::Float, "inf"), None);
+        assert_eq!(parse(PropPrimitiveType::Double, "1.0"), Some("1f64".into()));
+        assert_eq!(parse(PropPrimitiveType::Str, "text"), None);
+    }
+
+    #[test]
+    fn none_objects() {
+        let object = PropertyType::Object("Actor");
+        let parse =
+            |nullable, value| parse(&object, nullable, value).map(|tokens| tokens.to_string());
+        assert_eq!(
+            parse(true, "None"),
+            Some(quote! { ::core::option::Option::None }.to_string())
+        );
+        assert_eq!(
+            parse(true, ""),
+            Some(quote! { ::core::option::Option::None }.to_string())
+        );
+        // a non-nullable object has no default
+        assert_eq!(parse(false, "None"), None);
+    }
+
+    #[test]
+    fn structs_and_enums_have_no_default() {
+        // their types are not generated yet
+        assert!(parse(&PropertyType::Struct("Vector"), false, "(X=1,Y=2,Z=3)").is_none());
+        assert!(parse(&PropertyType::Enum("EMode"), false, "EMode::Flying").is_none());
+    }
+}
@@ -36,8 +36,7 @@ fn insert_struct_symbol<'a>(
     let mut contains_lifetime = false;
     for member in &struct_def.members {
         contains_lifetime = match &member.prop_type {
-            PropertyType::Enum(_) | PropertyType::Primitive(_) => false,
-            PropertyType::Object(_) => true,
+            PropertyType::Enum(_) | PropertyType::Primitive(_) | PropertyType::Object(_) => false,
             &PropertyType::Struct(prop_struct_name) => match struct_table.get(prop_struct_name) {
                 Some(struct_state) => match struct_state {
                     StructState::Resolved { contains_lifetime } => *contains_lifetime,
@@ -1,13 +1,15 @@
 use crate::{
-    DefClass, DefFunction, DefProperty, EPropertyFlag,
-    codegen::{SymbolMap, resolve_property::ResolvedTypeOfProperty},
+    DefClass, DefFunction, DefProperty, EFunctionFlag, EPropertyFlag, Options, OutputStyle,
+    PropertyType,
+    codegen::{SymbolMap, default_value, resolve_property::ResolvedTypeOfProperty},
 };
 use anyhow::Result;
 use case::CaseExt;
-use quote::format_ident;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
 use syn::Ident;
 
-use crate::codegen::SafeNameCast;
+use crate::codegen::{LifetimeConst, SafeNameCast};
 
 pub(super) struct FunctionsCodeGen<'r> {
     symbols: &'r SymbolMap<'r>,
@@ -15,18 +17,18 @@ pub(super) struct FunctionsCodeGen<'r> {
     safe_param_names: SafeNameCast,
     params: Vec<FnParam<'r>>,
     out_param_contains_lifetime: bool,
-    out_param_count: OutParamCount,
+    options: Options,
 }
 
 impl<'r> FunctionsCodeGen<'r> {
-    pub fn new(symbols: &'r SymbolMap<'r>) -> Self {
+    pub fn new(symbols: &'r SymbolMap<'r>, options: Options) -> Self {
         Self {
             symbols,
             safe_function_names: SafeNameCast::new(),
             safe_param_names: SafeNameCast::new(),
             params: Vec::new(),
             out_param_contains_lifetime: false,
-            out_param_count: OutParamCount::Zero,
+            options,
         }
     }
 
@@ -41,21 +43,67 @@ impl<'r> FunctionsCodeGen<'r> {
             id,
             name: fn_name,
             rust_override,
+            flags,
+            parms_size,
+            world_context,
+            latent_info,
             ref params,
         } in &def_class.functions
         {
+            // events are implemented by Blueprint, and other functions like RPCs and delegate
+            // signatures are not meant to be called from Blueprint either
+            if flags & EFunctionFlag::BlueprintCallable == 0 {
+                continue;
+            }
+            if flags & EFunctionFlag::EditorOnly != 0 && !self.options.editor_only {
+                continue;
+            }
             self.start_define_function();
 
             let safe_func_name = self.define_name(fn_name);
-            for (index, param) in params.iter().enumerate() {
-                self.define_param(index, param)?;
+            let try_fn_name = self.define_name(&format!("try_{fn_name}"));
+            for param in params {
+                let filled_in = if world_context == Some(param.name) {
+                    Some(ParamKind::WorldContext)
+                } else if latent_info == Some(param.name) {
+                    Some(ParamKind::LatentInfo)
+                } else {
+                    None
+                };
+                self.define_param(param, filled_in)?;
+            }
+
+            // the reference parameters of a latent function stay in its frame until UE completes
+            // the action, dropping the future before would leave the caller with a stale copy
+            if self.params.iter().any(|p| p.kind == ParamKind::LatentInfo)
+                && self
+                    .params
+                    .iter()
+                    .any(|p| p.kind == ParamKind::InOut && p.needs_destroy)
+            {
+                println!(
+                    "cargo:warning=latent function `{}::{fn_name}` is skipped, it takes a reference parameter with a destructor",
+                    def_class.name
+                );
+                continue;
             }
 
+            let with_defaults_fn_name = self
+                .params
+                .iter()
+                .any(|p| p.default.is_some())
+                .then(|| self.define_name(&format!("{fn_name}_with_defaults")));
+
             let return_type = self.get_return_type(&safe_func_name);
             output.push(FunctionInst {
                 fn_name: safe_func_name,
+                try_fn_name,
+                with_defaults_fn_name,
                 id,
+                pure: flags & EFunctionFlag::BlueprintPure != 0,
+                is_static: flags & EFunctionFlag::Static != 0,
                 params: std::mem::take(&mut self.params),
+                parms_size,
                 return_type,
             });
         }
@@ -71,77 +119,187 @@ impl<'r> FunctionsCodeGen<'r> {
         self.safe_param_names.clear();
         self.params.clear();
         self.out_param_contains_lifetime = false;
-        self.out_param_count = OutParamCount::Zero;
     }
 
     fn define_name(&mut self, fn_name: &str) -> Ident {
         self.safe_function_names.to_safe_name(fn_name)
     }
 
-    fn define_param(&mut self, index: usize, param: &DefProperty) -> Result<()> {
-        let is_output = param.flags & (EPropertyFlag::OutParm | EPropertyFlag::ReturnParm) != 0;
+    /// `filled_in` is the kind of an input named by the function metadata, which the wrapper
+    /// fills in instead of taking it as an argument.
+    fn define_param(&mut self, param: &DefProperty, filled_in: Option<ParamKind>) -> Result<()> {
         let param_name = self.safe_param_names.to_safe_name(&param.name.to_snake());
         let param_type = self.symbols.get_type_of_property(&param.prop_type)?;
+        let kind = match (ParamKind::of(param.flags), filled_in) {
+            (ParamKind::In, Some(ParamKind::WorldContext)) if param_type.is_object() => {
+                ParamKind::WorldContext
+            }
+            (ParamKind::In, Some(ParamKind::LatentInfo))
+                if matches!(param.prop_type, PropertyType::Struct(_)) =>
+            {
+                ParamKind::LatentInfo
+            }
+            (kind, _) => kind,
+        };
 
-        if is_output {
+        if kind.is_output() {
             self.out_param_contains_lifetime |= param_type.contains_lifetime();
-            self.out_param_count = match self.out_param_count {
-                OutParamCount::Zero => OutParamCount::One { index },
-                _ => OutParamCount::Many,
-            };
+        };
+
+        let non_nullable = param.flags & EPropertyFlag::NonNullable != 0;
+        let default = match (kind, &param.default) {
+            (ParamKind::In | ParamKind::ConstRef, Some(value)) => {
+                default_value::parse(&param.prop_type, !non_nullable, value)
+            }
+            _ => None,
         };
 
         self.params.push(FnParam {
             name: param_name,
             ty: param_type,
-            is_out: is_output,
+            kind,
+            offset: param.offset,
+            size: param.size,
+            needs_init: param.flags & EPropertyFlag::ZeroConstructor == 0,
+            needs_destroy: param.flags
+                & (EPropertyFlag::NoDestructor | EPropertyFlag::IsPlainOldData)
+                == 0,
+            non_nullable,
+            default,
         });
 
         Ok(())
     }
 
-    fn get_return_type(&mut self, fn_name: &Ident) -> ReturnType<'r> {
-        match self.out_param_count {
-            OutParamCount::Zero => ReturnType::None,
-            OutParamCount::One { index } => {
-                let p = &self.params[index];
-                ReturnType::Single(p.name.clone(), p.ty)
-            }
-            OutParamCount::Many => ReturnType::Multiple(ReturnStruct {
+    fn get_return_type(&mut self, fn_name: &Ident) -> ReturnType {
+        // the return value first, then the out params in declaration order, so adding an out
+        // param to a function only appends to its outputs
+        let outputs: Vec<usize> = [ParamKind::Return, ParamKind::Out]
+            .into_iter()
+            .flat_map(|kind| {
+                self.params
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(index, p)| (p.kind == kind).then_some(index))
+            })
+            .collect();
+
+        match (outputs.as_slice(), self.options.output_style) {
+            ([], _) => ReturnType::None,
+            (&[index], _) => ReturnType::Single(index),
+            (_, OutputStyle::Tuple) => ReturnType::Tuple(outputs),
+            (_, OutputStyle::Struct) => ReturnType::Struct(ReturnStruct {
                 struct_name: format_ident!("BPRustReturnTypeOf{fn_name}"),
                 contains_lifetime: self.out_param_contains_lifetime,
+                fields: outputs,
             }),
         }
     }
 }
 
-#[derive(Clone, Copy)]
-enum OutParamCount {
-    Zero,
-    One { index: usize },
-    Many,
-}
-
 pub struct FunctionInst<'r> {
     pub fn_name: Ident,
+    /// Name of the variant returning a `Result` instead of panicking.
+    pub try_fn_name: Ident,
+    /// Name of the variant taking only the inputs without a default value, if any input has one.
+    pub with_defaults_fn_name: Option<Ident>,
     pub id: &'r str,
+    /// `BlueprintPure`, calling it only to discard the outputs is a mistake.
+    pub pure: bool,
+    /// A static function, generated as an associated function called on the class default
+    /// object. It has no object to fall back to as world context.
+    pub is_static: bool,
     pub params: Vec<FnParam<'r>>,
-    pub return_type: ReturnType<'r>,
+    /// `ParmsSize` of the `UFunction`.
+    pub parms_size: usize,
+    pub return_type: ReturnType,
 }
 
-pub enum ReturnType<'r> {
+/// Outputs are indices of the parameters in `FunctionInst::params`.
+pub enum ReturnType {
     None,
-    Single(Ident, ResolvedTypeOfProperty<'r>),
-    Multiple(ReturnStruct),
+    Single(usize),
+    Tuple(Vec<usize>),
+    Struct(ReturnStruct),
 }
 
 pub struct ReturnStruct {
     pub struct_name: Ident,
     pub contains_lifetime: bool,
+    pub fields: Vec<usize>,
 }
 
 pub struct FnParam<'r> {
     pub name: Ident,
     pub ty: ResolvedTypeOfProperty<'r>,
-    pub is_out: bool,
+    pub kind: ParamKind,
+    /// Offset in the parameter memory of the `UFunction`.
+    pub offset: usize,
+    pub size: usize,
+    /// The property is not `ZeroConstructor`, UE has to construct it.
+    pub needs_init: bool,
+    /// The property has a destructor UE has to run.
+    pub needs_destroy: bool,
+    /// An object property that is never null.
+    pub non_nullable: bool,
+    /// Expression of the default value of an input.
+    pub default: Option<TokenStream>,
+}
+
+impl FnParam<'_> {
+    /// Object types are `Option`s unless the property is `NonNullable`.
+    pub fn type_tokens(&self, lifetime: LifetimeConst) -> TokenStream {
+        let ty = self.ty.type_tokens(lifetime);
+        if self.ty.is_object() && !self.non_nullable {
+            quote! { ::core::option::Option<#ty> }
+        } else {
+            ty
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    /// Taken by value and moved into the frame.
+    In,
+    /// The return value.
+    Return,
+    /// Returned after the return value.
+    Out,
+    /// `UPARAM(ref)`, taken as `&mut T`, moved into the frame and back after the call.
+    InOut,
+    /// `const T&`, taken as `&T` and copied bitwise into the frame. UE never modifies or
+    /// destroys it, so the copy is just forgotten.
+    ConstRef,
+    /// The object in the `WorldContext` metadata, filled in from `bprust_sys::BpContext`.
+    WorldContext,
+    /// The `FLatentActionInfo` in the `LatentInfo` metadata, filled in by
+    /// `bprust_sys::cpp_import::LatentCall`. Its function starts the call and returns a future
+    /// completing with it.
+    LatentInfo,
+}
+
+impl ParamKind {
+    fn of(flags: i64) -> Self {
+        if flags & EPropertyFlag::ReturnParm != 0 {
+            Self::Return
+        } else if flags & EPropertyFlag::OutParm == 0 {
+            Self::In
+        } else if flags & EPropertyFlag::ReferenceParm == 0 {
+            Self::Out
+        } else if flags & EPropertyFlag::ConstParm != 0 {
+            // UE marks `const T&` as an out param to pass it by reference
+            Self::ConstRef
+        } else {
+            Self::InOut
+        }
+    }
+
+    pub fn is_input(self) -> bool {
+        matches!(self, Self::In | Self::InOut | Self::ConstRef)
+    }
+
+    pub fn is_output(self) -> bool {
+        matches!(self, Self::Return | Self::Out)
+    }
 }
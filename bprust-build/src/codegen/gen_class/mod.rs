@@ -1,4 +1,7 @@
-use std::ffi::{CStr, CString};
+use std::{
+    collections::HashSet,
+    ffi::{CStr, CString},
+};
 
 use anyhow::Result;
 use proc_macro2::TokenStream;
@@ -8,9 +11,9 @@ use syn::Ident;
 use crate::{
     DefClass, DefFunction,
     codegen::{
-        Codegen, ContentDefinition, INPUT_LIFETIME, OUTPUT_LIFETIME,
+        Codegen, ContentDefinition, OUTPUT_LIFETIME,
         gen_class::gen_functions::{
-            FnParam, FunctionInst, FunctionsCodeGen, ReturnStruct, ReturnType,
+            FnParam, FunctionInst, FunctionsCodeGen, ParamKind, ReturnStruct, ReturnType,
         },
         lifetime_const::LifetimeConst,
     },
@@ -21,22 +24,61 @@ mod gen_functions;
 impl<'a> Codegen<'a> {
     pub fn gen_class(&self, class: &DefClass<'a>) -> Result<TokenStream> {
         let class_name = &self.symbols.lookup_name(class.id).unwrap().safe_name;
-        let functions = FunctionsCodeGen::new(&self.symbols).generate_functions(&class)?;
+        let functions =
+            FunctionsCodeGen::new(&self.symbols, self.options).generate_functions(&class)?;
+
+        let Ok(path) = CString::new(class.path) else {
+            panic!("cannot generate class path `{}`", class.path)
+        };
 
         let codegen = ClassCodeGen {
             class_name,
             id: class.id,
+            name: class.name,
+            package: class.package,
+            path,
+            super_class: self.lookup_class(class.super_id),
+            ancestors: self.ancestors_of(class.id),
             fn_return_structs_module_name: format_ident!("bprust_return_types_of_{class_name}"),
             functions,
         };
 
         Ok(codegen.to_tokens())
     }
+
+    fn lookup_class(&self, id: &str) -> Option<&Ident> {
+        self.symbols
+            .lookup_name(id)
+            .filter(|content| matches!(content.def, ContentDefinition::Class))
+            .map(|content| &content.safe_name)
+    }
+
+    /// Generated super classes of the class, from the direct super class to the root.
+    fn ancestors_of(&self, id: &str) -> Vec<&Ident> {
+        let mut ancestors = Vec::new();
+        let mut visited = HashSet::from([id]);
+        let mut current = id;
+        while let Some(&super_id) = self.class_supers.get(current) {
+            if !visited.insert(super_id) {
+                break;
+            }
+            if let Some(super_class) = self.lookup_class(super_id) {
+                ancestors.push(super_class);
+            }
+            current = super_id;
+        }
+        ancestors
+    }
 }
 
 struct ClassCodeGen<'r> {
     class_name: &'r Ident,
     id: &'r str,
+    name: &'r str,
+    package: &'r str,
+    path: CString,
+    super_class: Option<&'r Ident>,
+    ancestors: Vec<&'r Ident>,
     fn_return_structs_module_name: Ident,
     functions: Vec<FunctionInst<'r>>,
 }
@@ -46,13 +88,23 @@ impl ClassCodeGen<'_> {
         let Self {
             class_name,
             id,
+            name,
+            package,
+            path,
+            super_class,
+            ancestors,
             fn_return_structs_module_name,
             functions,
         } = self;
 
+        let super_class = match super_class {
+            Some(super_class) => super_class.to_token_stream(),
+            None => quote! { () },
+        };
+
         let function_definitions = functions
             .iter()
-            .map(|f| generate_function(f, fn_return_structs_module_name));
+            .map(|f| generate_function(f, name, fn_return_structs_module_name));
 
         let function_return_module = {
             let mut structs = functions
@@ -73,6 +125,22 @@ impl ClassCodeGen<'_> {
             pub struct #class_name(());
             #function_return_module
 
+            unsafe impl bprust_sys::UClassInfo for #class_name {
+                const ID: &'static str = #id;
+                const NAME: &'static str = #name;
+                const PACKAGE: &'static str = #package;
+                const PATH: &'static ::core::ffi::CStr = #path;
+
+                type Super = #super_class;
+
+                fn static_class() -> ::core::option::Option<*mut ()> {
+                    static CLASS: bprust_sys::StaticClassCache = bprust_sys::StaticClassCache::new();
+                    CLASS.get_or_find(Self::PATH)
+                }
+            }
+
+            #(unsafe impl bprust_sys::SubclassOf<#ancestors> for #class_name {})*
+
             impl #class_name {
                 #(#function_definitions)*
             }
@@ -80,123 +148,407 @@ impl ClassCodeGen<'_> {
     }
 }
 
-fn generate_function(func: &FunctionInst, ret_mod: &Ident) -> TokenStream {
+fn generate_function(func: &FunctionInst, class_name: &str, ret_mod: &Ident) -> TokenStream {
     let FunctionInst {
         fn_name,
+        try_fn_name,
+        with_defaults_fn_name,
         id,
+        pure,
+        is_static,
         params,
+        parms_size,
         return_type,
     } = func;
 
-    let arguments = params.iter().filter_map(|FnParam { name, ty, is_out }| {
-        if *is_out {
-            None
-        } else {
-            let ty = ty.type_tokens(LifetimeConst::Anonymous);
-            Some(quote! { #name: #ty, })
+    // a latent function completes when UE finishes its latent action, so it returns a future
+    let latent_info_offset = params
+        .iter()
+        .find(|p| p.kind == ParamKind::LatentInfo)
+        .map(|p| p.offset);
+    let latent = latent_info_offset.is_some();
+
+    let argument = |param: &FnParam| {
+        let FnParam { name, kind, .. } = param;
+        let ty = param.type_tokens(LifetimeConst::Anonymous);
+        match kind {
+            // moved back when the future completes, so it is borrowed as long as the future
+            ParamKind::InOut if latent => quote! { #name: &#OUTPUT_LIFETIME mut #ty, },
+            ParamKind::InOut => quote! { #name: &mut #ty, },
+            ParamKind::ConstRef => quote! { #name: &#ty, },
+            _ => quote! { #name: #ty, },
         }
-    });
+    };
+    let arguments: Vec<TokenStream> = params
+        .iter()
+        .filter(|p| p.kind.is_input())
+        .map(argument)
+        .collect();
 
+    let output_type = |index: &usize| params[*index].type_tokens(LifetimeConst::Output);
     let return_type = match return_type {
-        ReturnType::None => quote! {},
-        ReturnType::Single(_, ty) => {
-            let ty = ty.type_tokens(LifetimeConst::Output);
-            quote! { -> #ty }
+        ReturnType::None => quote! { () },
+        ReturnType::Single(index) => output_type(index),
+        ReturnType::Tuple(outputs) => {
+            let types = outputs.iter().map(output_type);
+            quote! { (#(#types),*) }
         }
-        ReturnType::Multiple(ReturnStruct {
+        ReturnType::Struct(ReturnStruct {
             struct_name,
             contains_lifetime,
             ..
         }) => {
             let lifetime = contains_lifetime.then(|| quote! {<#OUTPUT_LIFETIME>});
+            quote! { #ret_mod::#struct_name #lifetime }
+        }
+    };
+
+    // the future of a latent function borrows neither the object nor the inputs, it is started
+    // before it is returned
+    let output_of = |output: TokenStream| {
+        if latent {
             quote! {
-                -> #ret_mod::#struct_name #lifetime
+                impl ::core::future::Future<Output = #output> + use<#OUTPUT_LIFETIME>
             }
+        } else {
+            output
         }
     };
 
-    let param_struct_fields = params.iter().map(|FnParam { name, ty, is_out }| {
-        let ty = ty.type_tokens(if *is_out {
-            LifetimeConst::Output
-        } else {
-            LifetimeConst::Input
-        });
-        quote! { #name: ::core::mem::MaybeUninit<#ty>, }
+    let qualified_name = format!("{class_name}::{id}");
+
+    let must_use =
+        (*pure && !matches!(func.return_type, ReturnType::None)).then(|| quote! { #[must_use] });
+
+    let layout_asserts = params.iter().map(|param| {
+        let ty = param.type_tokens(LifetimeConst::Static);
+        let FnParam { offset, size, .. } = param;
+        let message = format!(
+            "the layout of parameter `{}` of `{qualified_name}` diverges from the exported definitions",
+            param.name
+        );
+        if param.kind == ParamKind::LatentInfo {
+            // written by the plugin, Rust only knows its size
+            return quote! {
+                assert!(
+                    #size == bprust_sys::cpp_import::LATENT_ACTION_INFO_SIZE
+                        && #offset + #size <= PARMS_SIZE,
+                    #message
+                );
+            };
+        }
+        quote! {
+            assert!(
+                ::core::mem::size_of::<#ty>() == #size
+                    && #offset % ::core::mem::align_of::<#ty>() == 0
+                    && #offset + #size <= PARMS_SIZE,
+                #message
+            );
+        }
     });
 
     let input_param_names: Vec<&Ident> = params
         .iter()
-        .filter_map(|p| (!p.is_out).then_some(&p.name))
+        .filter_map(|p| p.kind.is_input().then_some(&p.name))
         .collect();
 
-    let output_param_names: Vec<&Ident> = params
+    let frame_closure = |statements: Vec<TokenStream>| {
+        if statements.is_empty() {
+            quote! { |_| {} }
+        } else {
+            quote! { |params| { #(#statements)* } }
+        }
+    };
+
+    // a static function is called on the class default object. The object of a latent function
+    // may be collected before the action finishes, so it is only accessed to start the call.
+    let receiver = match (*is_static, latent) {
+        (true, _) => None,
+        (false, true) => Some(quote! { &self, }),
+        (false, false) => Some(quote! { &#OUTPUT_LIFETIME self, }),
+    };
+    let (object_binding, target) = if *is_static {
+        (
+            Some(quote! {
+                let this = <Self as bprust_sys::UClassInfo>::default_object()?;
+                // the class default object is never collected
+                let object = unsafe { this.get_unchecked() };
+            }),
+            quote! { object },
+        )
+    } else {
+        (None, quote! { self })
+    };
+    let call_self = |name: &Ident, arguments: TokenStream| {
+        if *is_static {
+            quote! { Self::#name(#arguments) }
+        } else {
+            quote! { self.#name(#arguments) }
+        }
+    };
+
+    // reference parameters are moved into the frame and back, as raw pointers so that both
+    // closures can capture them. Those of a latent function are moved back by its future.
+    let reference_pointers = params
+        .iter()
+        .filter(|p| !latent && p.kind == ParamKind::InOut)
+        .map(|FnParam { name, .. }| quote! { let #name: *mut _ = #name; });
+
+    // the object a static function is called on is not related to the world, so only the
+    // ambient context is passed
+    let static_world_context = params
+        .iter()
+        .filter(|p| *is_static && p.kind == ParamKind::WorldContext)
+        .map(|FnParam { name, .. }| {
+            quote! {
+                let #name = bprust_sys::BpContext::world_context()
+                    .ok_or(bprust_sys::BpCallError::NoWorldContext)?;
+            }
+        });
+
+    let write_inputs = frame_closure(
+        params
+            .iter()
+            .filter_map(
+                |FnParam {
+                     name, offset, kind, ..
+                 }| match kind {
+                    ParamKind::In => Some(quote! { params.write(#offset, #name); }),
+                    ParamKind::InOut | ParamKind::ConstRef => {
+                        Some(quote! { params.write(#offset, ::core::ptr::read(#name)); })
+                    }
+                    ParamKind::WorldContext if *is_static => {
+                        Some(quote! { params.write(#offset, #name); })
+                    }
+                    ParamKind::WorldContext => Some(quote! {
+                        params.write(#offset, bprust_sys::BpContext::world_context_or(#target));
+                    }),
+                    ParamKind::Return | ParamKind::Out | ParamKind::LatentInfo => None,
+                },
+            )
+            .collect(),
+    );
+
+    let copy_back_statements: Vec<TokenStream> = params
         .iter()
-        .filter_map(|p| p.is_out.then_some(&p.name))
+        .filter(|p| p.kind == ParamKind::InOut)
+        .map(|FnParam { name, offset, .. }| {
+            quote! { ::core::ptr::write(#name, params.read(#offset)); }
+        })
         .collect();
+    // UE keeps modifying the reference parameters of a latent function until it completes, so
+    // they are moved back after the call is awaited. If it fails to start, UE never touched
+    // them and the caller's values are still intact.
+    let copy_back = match latent_info_offset {
+        Some(_) => frame_closure(Vec::new()),
+        None => frame_closure(copy_back_statements.clone()),
+    };
+
+    let param_indices = |filter: fn(&FnParam) -> bool| {
+        params
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, p)| filter(p).then_some(index))
+            .collect::<Vec<_>>()
+    };
+    let init_indices = param_indices(|p| p.kind.is_output() && p.needs_init);
+    let destroy_indices = param_indices(|p| {
+        matches!(p.kind, ParamKind::In | ParamKind::LatentInfo) && p.needs_destroy
+    });
+    let param_ops = if init_indices.is_empty() && destroy_indices.is_empty() {
+        quote! { bprust_sys::cpp_import::ParamOps::NONE }
+    } else {
+        quote! {
+            bprust_sys::cpp_import::ParamOps {
+                init: &[#(#init_indices),*],
+                destroy: &[#(#destroy_indices),*],
+            }
+        }
+    };
+
+    let read_output = |index: &usize| {
+        let offset = params[*index].offset;
+        quote! { params.read(#offset) }
+    };
 
     let Ok(ufunc_name) = CString::new(*id) else {
         panic!("cannot generate function name `{id}`")
     };
 
     let return_expr = match &func.return_type {
-        ReturnType::None => quote! {},
-        ReturnType::Single(ident, _) => quote! {
-            params.#ident.assume_init()
-        },
-        ReturnType::Multiple(ReturnStruct { struct_name, .. }) => quote! {
-            #ret_mod::#struct_name {
-                #(#output_param_names: params.#output_param_names.assume_init(),)*
+        ReturnType::None => quote! { () },
+        ReturnType::Single(index) => read_output(index),
+        ReturnType::Tuple(outputs) => {
+            let values = outputs.iter().map(read_output);
+            quote! { (#(#values),*) }
+        }
+        ReturnType::Struct(ReturnStruct {
+            struct_name,
+            fields,
+            ..
+        }) => {
+            let fields = fields.iter().map(|index| {
+                let name = &params[*index].name;
+                let value = read_output(index);
+                quote! { #name: #value, }
+            });
+            quote! {
+                #ret_mod::#struct_name {
+                    #(#fields)*
+                }
+            }
+        }
+    };
+
+    let call_function = quote! {
+        bprust_sys::cpp_import::process_event_with_function(
+            #target,
+            &FUNCTION,
+            params,
+            &#param_ops,
+            #write_inputs,
+            #copy_back,
+        )
+    };
+    let call = match latent_info_offset {
+        None => quote! {
+            #object_binding
+            #(#static_world_context)*
+            #(#reference_pointers)*
+            let mut params = bprust_sys::cpp_import::ParamFrame::<PARMS_SIZE>::new();
+            let params = &mut params;
+            unsafe {
+                #call_function?;
+                ::core::result::Result::Ok(#return_expr)
             }
         },
+        Some(offset) => {
+            let read_outputs = match func.return_type {
+                ReturnType::None if copy_back_statements.is_empty() => quote! {
+                    call?.await;
+                    ::core::result::Result::Ok(())
+                },
+                _ => quote! {
+                    let params = call?.await;
+                    unsafe {
+                        #(#copy_back_statements)*
+                        ::core::result::Result::Ok(#return_expr)
+                    }
+                },
+            };
+            // the world context is only set while the caller runs, so the call is started
+            // before the future is returned
+            quote! {
+                let call = (|| -> ::core::result::Result<
+                    bprust_sys::cpp_import::LatentCall<PARMS_SIZE>,
+                    bprust_sys::BpCallError,
+                > {
+                    #object_binding
+                    #(#static_world_context)*
+                    let mut call = bprust_sys::cpp_import::LatentCall::<PARMS_SIZE>::new()?;
+                    unsafe {
+                        call.start(#offset, |params| #call_function)?;
+                    }
+                    ::core::result::Result::Ok(call)
+                })();
+                async move {
+                    #read_outputs
+                }
+            }
+        }
     };
 
-    quote! {
-        pub fn #fn_name<#OUTPUT_LIFETIME>(&#OUTPUT_LIFETIME self, #(#arguments)*) #return_type {
-            #[repr(C)]
-            struct __BPRustFunctionParameters<#INPUT_LIFETIME, #OUTPUT_LIFETIME> {
-                _capture_lifetime: ::core::marker::PhantomData<(
-                    &#INPUT_LIFETIME (),
-                    &#OUTPUT_LIFETIME (),
-                )>,
-                #(#param_struct_fields)*
+    let with_defaults = with_defaults_fn_name.as_ref().map(|with_defaults_fn_name| {
+        let required_arguments = params
+            .iter()
+            .filter(|p| p.kind.is_input() && p.default.is_none())
+            .map(argument);
+        let values =
+            params
+                .iter()
+                .filter(|p| p.kind.is_input())
+                .map(|p| match (&p.default, p.kind) {
+                    (Some(default), ParamKind::ConstRef) => quote! { &#default },
+                    (Some(default), _) => default.clone(),
+                    (None, _) => p.name.to_token_stream(),
+                });
+        let call = call_self(fn_name, quote! { #(#values),* });
+        let with_defaults_output = output_of(return_type.clone());
+        quote! {
+            #must_use
+            pub fn #with_defaults_fn_name<#OUTPUT_LIFETIME>(
+                #receiver
+                #(#required_arguments)*
+            ) -> #with_defaults_output {
+                #call
             }
+        }
+    });
 
-            let mut params = __BPRustFunctionParameters {
-                _capture_lifetime: ::core::marker::PhantomData,
-                #(#input_param_names: ::core::mem::MaybeUninit::new(#input_param_names),)*
-                #(#output_param_names: ::core::mem::MaybeUninit::uninit(),)*
+    let call_try = call_self(try_fn_name, quote! { #(#input_param_names,)* });
+    let unwrap = |output: TokenStream| {
+        quote! {
+            match #output {
+                ::core::result::Result::Ok(output) => output,
+                ::core::result::Result::Err(err) => panic!("BPRust ERROR: calling `{}` failed: {}", #qualified_name, err),
+            }
+        }
+    };
+    let unwrap_try = if latent {
+        let unwrap = unwrap(quote! { output.await });
+        quote! {
+            let output = #call_try;
+            async move { #unwrap }
+        }
+    } else {
+        unwrap(call_try)
+    };
+    let fn_output = output_of(return_type.clone());
+    let try_fn_output =
+        output_of(quote! { ::core::result::Result<#return_type, bprust_sys::BpCallError> });
+
+    quote! {
+        #with_defaults
+
+        #must_use
+        pub fn #fn_name<#OUTPUT_LIFETIME>(#receiver #(#arguments)*) -> #fn_output {
+            #unwrap_try
+        }
+
+        pub fn #try_fn_name<#OUTPUT_LIFETIME>(
+            #receiver
+            #(#arguments)*
+        ) -> #try_fn_output {
+            const PARMS_SIZE: usize = #parms_size;
+            const _: () = {
+                #(#layout_asserts)*
             };
 
-            unsafe {
-                bprust_sys::cpp_import::process_event(
-                    self,
-                    #ufunc_name,
-                    &mut params,
-                );
-                #return_expr
-            }
+            static FUNCTION: bprust_sys::cpp_import::UFunctionCache =
+                bprust_sys::cpp_import::UFunctionCache::new(#ufunc_name);
+
+            #call
         }
     }
 }
 
 fn generate_function_return_struct(func: &FunctionInst) -> Option<TokenStream> {
-    let ReturnType::Multiple(ReturnStruct {
+    let ReturnType::Struct(ReturnStruct {
         struct_name,
         contains_lifetime,
+        fields,
     }) = &func.return_type
     else {
         return None;
     };
 
-    let out_params = func.params.iter().filter_map(|func| {
-        if !func.is_out {
-            return None;
-        }
-        let name = &func.name;
-        let ty = func.ty.type_tokens(LifetimeConst::Output);
-        Some(quote! {
+    let out_params = fields.iter().map(|index| {
+        let param = &func.params[*index];
+        let name = &param.name;
+        let ty = param.type_tokens(LifetimeConst::Output);
+        quote! {
             pub #name: #ty,
-        })
+        }
     });
 
     let lifetime_generic = contains_lifetime.then_some(LifetimeConst::Output);
@@ -5,8 +5,9 @@ use proc_macro2::{Ident, TokenStream};
 use quote::{ToTokens, TokenStreamExt, quote};
 
 use self::lifetime_const::*;
-use crate::{BPDefinitions, DefStruct, PropertyType, codegen::safe_name::SafeNameCast};
+use crate::{BPDefinitions, DefStruct, Options, PropertyType, codegen::safe_name::SafeNameCast};
 
+mod default_value;
 mod define_struct;
 mod gen_class;
 mod gen_struct;
@@ -14,10 +15,15 @@ mod lifetime_const;
 mod resolve_property;
 mod safe_name;
 
-pub fn generate_rust_code(definitions: BPDefinitions, prettify: bool) -> Result<String> {
-    let mut codegen = Codegen::new();
+pub fn generate_rust_code(
+    definitions: BPDefinitions,
+    definitions_hash: u64,
+    prettify: bool,
+    options: Options,
+) -> Result<String> {
+    let mut codegen = Codegen::new(options);
     codegen.define_symbols(&definitions)?;
-    let tokens = codegen.generate_code(&definitions)?;
+    let tokens = codegen.generate_code(&definitions, definitions_hash)?;
 
     let mut token_string = tokens.to_string();
     if prettify {
@@ -36,15 +42,20 @@ enum ContentDefinition {
 
 pub(crate) struct Codegen<'a> {
     symbols: SymbolMap<'a>,
+    /// class id -> super class id
+    class_supers: HashMap<&'a str, &'a str>,
+    options: Options,
 }
 
 impl<'a> Codegen<'a> {
-    pub fn new() -> Self {
+    pub fn new(options: Options) -> Self {
         Self {
             symbols: SymbolMap {
                 symbols: HashMap::new(),
                 safe_name: SafeNameCast::new(),
             },
+            class_supers: HashMap::new(),
+            options,
         }
     }
 
@@ -52,6 +63,9 @@ impl<'a> Codegen<'a> {
         for class in &definitions.classes {
             self.symbols
                 .resolve_insert(class.id, class.name, ContentDefinition::Class);
+            if !class.super_id.is_empty() {
+                self.class_supers.insert(class.id, class.super_id);
+            }
         }
         for enum_def in &definitions.enums {
             self.symbols
@@ -61,9 +75,17 @@ impl<'a> Codegen<'a> {
         Ok(())
     }
 
-    fn generate_code(&mut self, definitions: &BPDefinitions<'a>) -> Result<TokenStream> {
+    fn generate_code(
+        &mut self,
+        definitions: &BPDefinitions<'a>,
+        definitions_hash: u64,
+    ) -> Result<TokenStream> {
         let mut tokens = quote! {
             #![allow(nonstandard_style)]
+
+            /// Hash of the definitions these bindings are generated from,
+            /// pass it to `bprust_sys::register_library!`.
+            pub const BPRUST_DEFINITIONS_HASH: u64 = #definitions_hash;
         };
 
         for class in &definitions.classes {
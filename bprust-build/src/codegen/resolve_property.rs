@@ -35,7 +35,7 @@ impl ResolvedTypeOfProperty<'_> {
     pub fn contains_lifetime(&self) -> bool {
         match self {
             Self::Symbol(lc) => match lc.def {
-                ContentDefinition::Class => true,
+                ContentDefinition::Class => false,
                 ContentDefinition::Enum => false,
                 ContentDefinition::Struct { contains_lifetime } => contains_lifetime,
             },
@@ -44,6 +44,10 @@ impl ResolvedTypeOfProperty<'_> {
         }
     }
 
+    pub fn is_object(&self) -> bool {
+        matches!(self, Self::Symbol(lc) if matches!(lc.def, ContentDefinition::Class))
+    }
+
     pub fn type_tokens(&self, lifetime: LifetimeConst) -> TokenStream {
         let linked_content = match self {
             Self::Primitive(prim) => return prim_to_tokens(*prim),
@@ -55,7 +59,7 @@ impl ResolvedTypeOfProperty<'_> {
         match linked_content.def {
             ContentDefinition::Enum => symbol_name.to_token_stream(),
             ContentDefinition::Class => {
-                quote! { &#lifetime #symbol_name }
+                quote! { bprust_sys::UObjectRef<#symbol_name> }
             }
             ContentDefinition::Struct { contains_lifetime } => {
                 let lifetime_tokens = contains_lifetime.then(|| {
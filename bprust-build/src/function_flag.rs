@@ -0,0 +1,76 @@
+macro_rules! func_flag {
+    ($(#[$attr:meta] $Ident:ident = $value:expr,)*) => {
+        $(
+            #[$attr]
+            #[allow(unused, non_upper_case_globals)]
+            pub const $Ident: u32 = $value;
+        )*
+    };
+}
+
+func_flag! {
+    /// No flags.
+    None							= 0x00000000,
+    /// Function is final (prebindable, non-overridable function).
+    Final							= 0x00000001,
+    /// Indicates this function is DLL exported/imported.
+    RequiredAPI						= 0x00000002,
+    /// Function will only run if the object has network authority
+    BlueprintAuthorityOnly			= 0x00000004,
+    /// Function is cosmetic in nature and should not be invoked on dedicated servers
+    BlueprintCosmetic				= 0x00000008,
+    /// Function is network-replicated.
+    Net								= 0x00000040,
+    /// Function should be sent reliably on the network.
+    NetReliable						= 0x00000080,
+    /// Function is sent to a net service
+    NetRequest						= 0x00000100,
+    /// Executable from command line.
+    Exec							= 0x00000200,
+    /// Native function.
+    Native							= 0x00000400,
+    /// Event function.
+    Event							= 0x00000800,
+    /// Function response from a net service
+    NetResponse						= 0x00001000,
+    /// Static function.
+    Static							= 0x00002000,
+    /// Function is networked multicast Server -> All Clients
+    NetMulticast					= 0x00004000,
+    /// Function is used as the merge 'ubergraph' for a blueprint, only assigned when using the persistent 'ubergraph' frame
+    UbergraphFunction				= 0x00008000,
+    /// Function is a multi-cast delegate signature (also requires FUNC_Delegate to be set!)
+    MulticastDelegate				= 0x00010000,
+    /// Function is accessible in all classes (if overridden, parameters must remain unchanged).
+    Public							= 0x00020000,
+    /// Function is accessible only in the class it is defined in (cannot be overridden, but function name may be reused in subclasses.  IOW: if overridden, parameters don't need to match, and Super.Func() cannot be accessed since it's private.)
+    Private							= 0x00040000,
+    /// Function is accessible only in the class it is defined in and subclasses (if overridden, parameters much remain unchanged).
+    Protected						= 0x00080000,
+    /// Function is delegate signature (either single-cast or multi-cast, depending on whether FUNC_MulticastDelegate is set.)
+    Delegate						= 0x00100000,
+    /// Function is executed on servers (set by replication code if passes check)
+    NetServer						= 0x00200000,
+    /// function has out (pass by reference) parameters
+    HasOutParms						= 0x00400000,
+    /// function has structs that contain defaults
+    HasDefaults						= 0x00800000,
+    /// function is executed on clients
+    NetClient						= 0x01000000,
+    /// function is imported from a DLL
+    DLLImport						= 0x02000000,
+    /// function can be called from blueprint code
+    BlueprintCallable				= 0x04000000,
+    /// function can be overridden/implemented from a blueprint
+    BlueprintEvent					= 0x08000000,
+    /// function can be called from blueprint code, and is also pure (produces no side effects). If you set this, you should set FUNC_BlueprintCallable as well.
+    BlueprintPure					= 0x10000000,
+    /// function can only be called from an editor script.
+    EditorOnly						= 0x20000000,
+    /// function can be called from blueprint code, and only reads state (never writes state)
+    Const							= 0x40000000,
+    /// function must supply a _Validate implementation
+    NetValidate						= 0x80000000,
+    /// All flags.
+    AllFlags						= 0xFFFFFFFF,
+}
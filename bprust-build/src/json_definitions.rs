@@ -14,8 +14,11 @@ pub(crate) struct BPDefinitions<'a> {
 pub(crate) struct DefClass<'a> {
     pub name: &'a str,
     pub id: &'a str,
+    pub package: &'a str,
+    pub path: &'a str,
     #[serde(rename = "super")]
     pub super_class: String,
+    pub super_id: &'a str,
     pub properties: Vec<DefProperty<'a>>,
     pub functions: Vec<DefFunction<'a>>,
 }
@@ -45,6 +48,16 @@ pub(crate) struct DefFunction<'a> {
     pub name: &'a str,
     #[serde(rename = "override", default)]
     pub rust_override: bool,
+    /// `EFunctionFlags` of the `UFunction`, see `EFunctionFlag`.
+    pub flags: u32,
+    /// `ParmsSize` of the `UFunction`, the size of its parameter memory.
+    pub parms_size: usize,
+    /// Name of the parameter in the `WorldContext` metadata.
+    #[serde(default)]
+    pub world_context: Option<&'a str>,
+    /// Name of the `FLatentActionInfo` parameter of a function with `Latent` metadata.
+    #[serde(default)]
+    pub latent_info: Option<&'a str>,
     pub params: Vec<DefProperty<'a>>,
 }
 
@@ -54,6 +67,12 @@ pub(crate) struct DefProperty<'a> {
     #[serde(flatten)]
     pub prop_type: PropertyType<'a>,
     pub flags: i64,
+    /// Offset in the owning struct, or in the parameter memory of a function.
+    pub offset: usize,
+    pub size: usize,
+    /// `CPP_Default_*` metadata of a function parameter.
+    #[serde(default)]
+    pub default: Option<String>,
 }
 
 #[derive(Clone, Deserialize)]
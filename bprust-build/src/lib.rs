@@ -10,18 +10,55 @@ use json_definitions::*;
 
 use crate::codegen::generate_rust_code;
 
+#[path = "function_flag.rs"]
+#[allow(non_snake_case)]
+mod EFunctionFlag;
 #[path = "property_flag.rs"]
 #[allow(non_snake_case)]
 mod EPropertyFlag;
 mod codegen;
 mod json_definitions;
 
+/// How generated functions return a return value together with out params, or several out
+/// params. The return value always comes first, then the out params in declaration order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputStyle {
+    /// A tuple, `(return_value, out_a, out_b)`.
+    #[default]
+    Tuple,
+    /// A `BPRustReturnTypeOf<Function>` struct with a field per output.
+    Struct,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    pub output_style: OutputStyle,
+    /// Generate the `EditorOnly` functions, which only exist in editor builds. Calling one in
+    /// a packaged game fails with `BpCallError::FunctionNotFound`.
+    pub editor_only: bool,
+}
+
 pub fn compile(json: &str, code_prettify: bool) -> Result<String> {
+    compile_with_options(json, code_prettify, Options::default())
+}
+
+pub fn compile_with_options(json: &str, code_prettify: bool, options: Options) -> Result<String> {
     let def: BPDefinitions = serde_json::from_str(json)?;
-    generate_rust_code(def, code_prettify)
+    generate_rust_code(def, definitions_hash(json), code_prettify, options)
+}
+
+/// FNV-1a hash of the definitions json, stable across builds and Rust versions.
+fn definitions_hash(json: &str) -> u64 {
+    json.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
 }
 
 pub fn build(json_path: impl AsRef<Path>, file_path: Option<&Path>) {
+    build_with_options(json_path, file_path, Options::default())
+}
+
+pub fn build_with_options(json_path: impl AsRef<Path>, file_path: Option<&Path>, options: Options) {
     let file = match read_to_string(json_path.as_ref()) {
         Ok(file) => file,
         Err(err) => {
@@ -29,7 +66,7 @@ pub fn build(json_path: impl AsRef<Path>, file_path: Option<&Path>) {
         }
     };
 
-    let code = compile(&file, true).expect("compile error");
+    let code = compile_with_options(&file, true, options).expect("compile error");
 
     let mut out_file = PathBuf::new();
     if let Ok(dir) = env::var("OUT_DIR") {
@@ -48,8 +85,7 @@ pub fn build(json_path: impl AsRef<Path>, file_path: Option<&Path>) {
         }
     }
 
-    if let Err(err) = std::fs::write(&out_file, code)
-    {
+    if let Err(err) = std::fs::write(&out_file, code) {
         panic!("cannot write code to file `{}`: {err}", out_file.display());
     }
 
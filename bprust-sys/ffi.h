@@ -1,3 +1,32 @@
+#pragma once
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Must match `CPP_FUNCTION_TABLE_VERSION` of bprust-sys. Bumped when existing entries of
+// `CppFunctionTable` or `BPRustLibraryEntry` change, or entries are added to `BPRustLibraryEntry`.
+// New entries of `CppFunctionTable` are only appended, and are optional (nullable).
+#define BPRUST_ABI_VERSION 3
+
+// Returned by every exported entry point, anything but `BPRustStatus_Ok` should be logged.
+enum BPRustStatus : int32_t
+{
+    BPRustStatus_Ok = 0,
+    BPRustStatus_AlreadyInitialized = 1,
+    BPRustStatus_NotInitialized = 2,
+    BPRustStatus_Panicked = 3,
+    BPRustStatus_IncompatibleTable = 4,
+};
+
+// Returned by `process_event`. When the call fails, the outputs in `params` must be left
+// untouched, Rust does not read them.
+enum BPRustCallStatus : int32_t
+{
+    BPRustCallStatus_Ok = 0,
+    BPRustCallStatus_InvalidObject = 1,
+    BPRustCallStatus_FunctionNotFound = 2,
+};
+
 struct Handler
 {
     void *context;
@@ -5,18 +34,193 @@ struct Handler
     void *z_param_result;
 };
 
+// Borrowed UTF-8 string, not null terminated.
+struct StrView
+{
+    const char *ptr;
+    size_t len;
+};
+
+// Opaque storage of a `FText`, 16 bytes with 8 bytes alignment.
+struct FText;
+// Opaque storage of a `FName`, 12 bytes with 4 bytes alignment.
+struct FName;
+// Opaque storage of a `FString`, 16 bytes with 8 bytes alignment.
+struct FString;
+// Opaque storage of a `FScriptArray`, 16 bytes with 8 bytes alignment.
+struct FScriptArray;
+// Opaque storage of a `FScriptSet`, 80 bytes with 8 bytes alignment.
+struct FScriptSet;
+// Opaque storage of a `FScriptMap`, 80 bytes with 8 bytes alignment.
+struct FScriptMap;
+
+enum FormatArgumentValueTag : uint32_t
+{
+    FormatArgumentValue_Int,
+    FormatArgumentValue_UInt,
+    FormatArgumentValue_Float,
+    FormatArgumentValue_Double,
+    FormatArgumentValue_Text,
+};
+
+struct FormatArgumentValue
+{
+    FormatArgumentValueTag tag;
+    union
+    {
+        int64_t int_value;
+        uint64_t uint_value;
+        float float_value;
+        double double_value;
+        const FText *text_value;
+    };
+};
+
+struct FormatNamedArgument
+{
+    StrView name;
+    FormatArgumentValue value;
+};
+
 struct CppFunctionTable
 {
     void (*handle_custom_thunk)(Handler *handler,
                                 void *user_data,
                                 void (*resolve_param)(void *user_data, Handler *handler),
                                 void (*call_function)(void *user_data, void *u_object));
-    void (*process_event)(void *u_object, const char *fn_name, void *params);
+    BPRustCallStatus (*process_event)(void *u_object, const char *fn_name, void *params);
+    // `IsValid(u_object)`, null is allowed.
+    bool (*is_valid)(void *u_object);
+    // Find the `UClass` by path name, loading it if needed. Returns null if not found.
+    void *(*find_class)(const char *path);
+    // `u_object->IsA(u_class)`, returns false if `u_object` is not valid.
+    bool (*object_is_a)(void *u_object, void *u_class);
+
+    // GC roots are kept by a `FGCObject` collector. Handles are never reused while alive,
+    // and the functions may be called from any thread. `u_object` may be null.
+    uint64_t (*gc_root_add)(void *u_object);
+    // Returns null if the object was destroyed explicitly.
+    void *(*gc_root_get)(uint64_t handle);
+    void (*gc_root_remove)(uint64_t handle);
+
+    // `out` is uninitialized memory, every function writing to `out` must construct it.
+    void (*text_from_string)(FText *out, StrView string);
+    void (*text_as_culture_invariant)(FText *out, StrView string);
+    // Returns false and leaves `out` uninitialized if the text is not found.
+    bool (*text_find)(FText *out, StrView name_space, StrView key);
+    void (*text_format)(FText *out,
+                        const FText *pattern,
+                        const FormatNamedArgument *args,
+                        size_t args_len);
+    // `write` may be called several times with consecutive UTF-8 chunks.
+    void (*text_to_string)(const FText *text,
+                           void *user_data,
+                           void (*write)(void *user_data, StrView chars));
+    void (*text_clone)(FText *out, const FText *text);
+    void (*text_drop)(FText *text);
+
+    // ---- optional entries, may be null ----
+
+    // Look up an optional function table by name, e.g. "BPRust.Log.v1". Returns null if the
+    // capability is not provided. Tables must stay alive until `BPRustSys_shutdown`.
+    const void *(*get_capability)(const char *name);
+    // `u_object->GetClass()`, null if `u_object` is not valid.
+    void *(*object_get_class)(void *u_object);
+    // `u_class->FindFunctionByName(fn_name)`, null if not found. Rust caches the result per
+    // class until `BPRustSys_shutdown`.
+    void *(*find_function)(void *u_class, const char *fn_name);
+    // `process_event` with a `UFunction` returned by `find_function`.
+    BPRustCallStatus (*process_event_with_function)(void *u_object, void *u_function, void *params);
+    // `InitializeValue_InContainer(params)` and `DestroyValue_InContainer(params)` of the
+    // parameters of `u_function` at `indices`, counting the `CPF_Parm` properties in declaration
+    // order. Rust calls them around `process_event_with_function` for parameters that are not
+    // `CPF_ZeroConstructor` or need a destructor.
+    void (*init_params)(void *u_function, void *params, const size_t *indices, size_t len);
+    void (*destroy_params)(void *u_function, void *params, const size_t *indices, size_t len);
+    // `new (out) FName(name, FNAME_Add)`.
+    void (*name_from_string)(FName *out, StrView name);
+    // Construct a `FLatentActionInfo` in `out` with a unique UUID, whose callback target calls
+    // `on_complete(id)` on the game thread once the latent action finishes. `id` is unique per
+    // library.
+    void (*latent_action_info)(void *out, uint64_t id, void (*on_complete)(uint64_t id));
+    // Forget the callback of the latent action `id` passed with `on_complete`, it must not be
+    // called afterwards. Ids of different libraries may be equal, `on_complete` tells them
+    // apart. Called when the call fails, and for every pending action on `shutdown`.
+    void (*latent_action_forget)(uint64_t id, void (*on_complete)(uint64_t id));
+    // Destructors of the values Rust moves out of parameter frames. The untyped containers
+    // only free their allocations, without destroying the elements.
+    void (*string_drop)(FString *string);
+    void (*script_array_drop)(FScriptArray *array);
+    void (*script_set_drop)(FScriptSet *set);
+    void (*script_map_drop)(FScriptMap *map);
+    // `u_class->GetDefaultObject()`, which static functions are called on.
+    void *(*class_default_object)(void *u_class);
 };
 
-extern "C"
+// ---- capabilities ----
+
+enum LogVerbosity : uint8_t
 {
+    LogVerbosity_Fatal = 1,
+    LogVerbosity_Error = 2,
+    LogVerbosity_Warning = 3,
+    LogVerbosity_Display = 4,
+    LogVerbosity_Log = 5,
+    LogVerbosity_Verbose = 6,
+    LogVerbosity_VeryVerbose = 7,
+};
 
-    void BPRustSys_init(CppFunctionTable table);
+// "BPRust.Log.v1"
+struct LogCapability
+{
+    void (*log)(LogVerbosity verbosity, StrView message);
+};
+
+// "BPRust.HotReload.v1", storage that outlives unloaded libraries. Keys are scoped by the
+// library name.
+struct HotReloadCapability
+{
+    // Store a copy of `data` under `key`, replacing the previous data.
+    void (*store)(StrView key, const uint8_t *data, size_t len);
+    // Pass the data stored under `key` to `write` and remove it. Returns false if nothing is
+    // stored.
+    bool (*take)(StrView key,
+                 void *user_data,
+                 void (*write)(void *user_data, const uint8_t *data, size_t len));
+};
+
+// ---- library entry ----
+
+// Every Rust library exports a single entry point `BPRustSys_<name>_entry`, where `<name>` is
+// the file name without `lib` prefix and extension, e.g. `my_game.dll` exports
+// `BPRustSys_my_game_entry`. Each library has its own function table and is initialized
+// independently.
+struct BPRustLibraryEntry
+{
+    // Check this against `BPRUST_ABI_VERSION` before reading anything else.
+    uint32_t abi_version;
+    const char *name;
+    const char *version;
+    // Hash of the definitions json the bindings are generated from, 0 if not provided.
+    uint64_t definitions_hash;
+
+    // Installs the table and runs the startup function of the library. Call as
+    // `init(&Table, BPRUST_ABI_VERSION, sizeof(CppFunctionTable))`, the table is copied.
+    BPRustStatus (*init)(const CppFunctionTable *table, uint32_t version, size_t size);
+    // Runs Rust shutdown hooks and releases everything Rust registered, must be called
+    // before the library is unloaded. The library may be initialized again afterwards.
+    BPRustStatus (*shutdown)();
+    // Static description of a status for logging.
+    const char *(*status_message)(BPRustStatus status);
+    // Saves the state the library persists across a hot reload through `HotReloadCapability`.
+    //
+    // A hot reload is `prepare_reload`, `shutdown`, unload, load and `init`. Registrations are
+    // torn down by `shutdown` and rebuilt by the startup function on `init`, so everything
+    // obtained from the library in between, e.g. function pointers and `user_data`, is stale
+    // after `shutdown` and must be forgotten before the library is unloaded.
+    BPRustStatus (*prepare_reload)();
+    // Polls the Rust tasks awaiting latent actions, called on the game thread every engine tick.
+    BPRustStatus (*tick)();
+};
 
-} // extern "C"
+typedef const BPRustLibraryEntry *(*BPRustSys_entry_fn)();
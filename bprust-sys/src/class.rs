@@ -0,0 +1,116 @@
+use std::ffi::CStr;
+
+use crate::{
+    BpCallError, UObjectRef,
+    cpp_import::{cpp_get, try_cpp_get},
+    lifecycle::GenerationCache,
+};
+
+/// Runtime type identity of a generated class.
+///
+/// # Safety
+/// `static_class` must return the `UClass` the implementing type was generated from.
+pub unsafe trait UClassInfo {
+    /// Object name of the `UClass`, e.g. `BP_Enemy_C`.
+    const ID: &'static str;
+    /// Authored name of the class, e.g. `BP_Enemy`.
+    const NAME: &'static str;
+    /// Package containing the class, e.g. `/Script/Engine`.
+    const PACKAGE: &'static str;
+    /// Path of the `UClass`, e.g. `/Script/Engine.Actor`.
+    const PATH: &'static CStr;
+
+    /// The generated super class, `()` if the class has no super class or it is not generated.
+    type Super;
+
+    /// The `UClass` object, looked up once and cached. `None` if the class is not found, e.g.
+    /// a Blueprint class that is not loaded or was removed.
+    fn static_class() -> Option<*mut ()>;
+
+    /// The class default object (CDO), which generated static functions are called on.
+    fn default_object() -> Result<UObjectRef<Self>, BpCallError>
+    where
+        Self: Sized,
+    {
+        let table = try_cpp_get().map_err(|_| BpCallError::NotInitialized)?;
+        let class_default_object = table.class_default_object.ok_or(BpCallError::Unsupported)?;
+        let class = Self::static_class().ok_or(BpCallError::ClassNotFound)?;
+        unsafe { UObjectRef::from_raw(class_default_object(class)) }
+            .ok_or(BpCallError::InvalidObject)
+    }
+}
+
+/// Marks `Self` as `Base` or a class derived from `Base`, which makes upcasting free.
+///
+/// # Safety
+/// `Base` must be `Self` or one of its super classes.
+pub unsafe trait SubclassOf<Base> {}
+
+unsafe impl<T> SubclassOf<T> for T {}
+
+/// Cache of a `UClass` found by path, used by generated `UClassInfo::static_class`.
+///
+/// The cache is dropped when BPRust shuts down, the class is looked up again after
+/// the next initialization. A class that is not found is looked up again on the next call,
+/// it may be loaded later.
+pub struct StaticClassCache {
+    class: GenerationCache,
+}
+
+impl StaticClassCache {
+    pub const fn new() -> Self {
+        Self {
+            class: GenerationCache::new(),
+        }
+    }
+
+    pub fn get_or_find(&self, path: &CStr) -> Option<*mut ()> {
+        if let Some(class) = self.class.get().filter(|class| !class.is_null()) {
+            return Some(class);
+        }
+
+        let class = unsafe { (cpp_get().find_class)(path.as_ptr()) };
+        if class.is_null() {
+            return None;
+        }
+        self.class.set(class);
+        Some(class)
+    }
+}
+
+impl Default for StaticClassCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> UObjectRef<T> {
+    /// Whether the object is an instance of `U` or a class derived from `U`, same as `IsA<U>()`.
+    /// False if the class of `U` is not found.
+    ///
+    /// # Safety
+    /// The object must be valid, see [`UObjectRef::is_valid`].
+    pub unsafe fn is_a<U: UClassInfo>(self) -> bool {
+        U::static_class()
+            .is_some_and(|class| unsafe { (cpp_get().object_is_a)(self.as_ptr(), class) })
+    }
+
+    /// Checked downcast, same as `Cast<U>()` in C++.
+    ///
+    /// # Safety
+    /// The object must be valid, see [`UObjectRef::is_valid`].
+    pub unsafe fn cast<U: UClassInfo>(self) -> Option<UObjectRef<U>> {
+        if unsafe { self.is_a::<U>() } {
+            unsafe { UObjectRef::from_raw(self.as_ptr()) }
+        } else {
+            None
+        }
+    }
+
+    pub fn upcast<U>(self) -> UObjectRef<U>
+    where
+        T: SubclassOf<U>,
+    {
+        unsafe { UObjectRef::from_raw(self.as_ptr()).unwrap_unchecked() }
+    }
+}
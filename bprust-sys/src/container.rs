@@ -0,0 +1,27 @@
+use crate::{FScriptArray, FScriptMap, FScriptSet, FString, cpp_import::try_cpp_get};
+
+// Outputs of Blueprint functions are moved out of the parameter frame, so Rust owns their
+// allocations. Without a table, or with a plugin that doesn't provide the destructor, the
+// value is leaked instead of panicking in drop.
+macro_rules! drop_through_table {
+    ($($Type:ident $entry:ident;)*) => {
+        $(
+            impl Drop for $Type {
+                fn drop(&mut self) {
+                    if let Some(drop) = try_cpp_get().ok().and_then(|table| table.$entry) {
+                        unsafe { drop(self) }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+drop_through_table! {
+    FString string_drop;
+    // the untyped containers can't destroy their elements, only elements without a
+    // destructor are released completely
+    FScriptArray script_array_drop;
+    FScriptSet script_set_drop;
+    FScriptMap script_map_drop;
+}
@@ -0,0 +1,52 @@
+use std::{cell::Cell, marker::PhantomData, ptr};
+
+use crate::UObjectRef;
+
+thread_local! {
+    static WORLD_CONTEXT: Cell<*mut ()> = const { Cell::new(ptr::null_mut()) };
+}
+
+/// The ambient world context of the current thread, passed by generated wrappers to the
+/// `WorldContextObject` parameters of functions like `UGameplayStatics::GetPlayerController`.
+///
+/// Entering a context lasts until the returned guard is dropped, contexts can be nested.
+/// Without a context, wrappers of member functions pass the object the function is called on,
+/// and static functions fail with `BpCallError::NoWorldContext`.
+#[must_use = "the world context is left when the guard is dropped"]
+pub struct BpContext {
+    previous: *mut (),
+    _not_send: PhantomData<*const ()>,
+}
+
+impl BpContext {
+    pub fn enter<T>(world_context: UObjectRef<T>) -> Self {
+        let previous = WORLD_CONTEXT.replace(world_context.as_ptr());
+        Self {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Run `f` with `world_context` as the ambient world context.
+    pub fn scope<T, R>(world_context: UObjectRef<T>, f: impl FnOnce() -> R) -> R {
+        let _context = Self::enter(world_context);
+        f()
+    }
+
+    /// The ambient world context, if any.
+    pub fn world_context() -> Option<*mut ()> {
+        let world_context = WORLD_CONTEXT.get();
+        (!world_context.is_null()).then_some(world_context)
+    }
+
+    /// The ambient world context, or `object` if there is none.
+    pub fn world_context_or<T>(object: &T) -> *mut () {
+        Self::world_context().unwrap_or(object as *const T as *mut ())
+    }
+}
+
+impl Drop for BpContext {
+    fn drop(&mut self) {
+        WORLD_CONTEXT.set(self.previous);
+    }
+}
@@ -0,0 +1,59 @@
+use std::{error::Error, fmt};
+
+/// Error of calling a Blueprint function through `ProcessEvent`. The output parameters are not
+/// written when the call fails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BpCallError {
+    /// The function table has not been installed by `LibraryEntry::init`.
+    NotInitialized,
+    /// The object is not valid, e.g. it is pending kill or has been garbage collected.
+    InvalidObject,
+    /// The class of the object has no function of this name, the definitions the bindings are
+    /// generated from are probably outdated.
+    FunctionNotFound,
+    /// The call needs function table entries the plugin does not provide, e.g. to construct
+    /// the parameters of the function or to start a latent action.
+    Unsupported,
+    /// The class of a static function is not found, so it has no class default object to be
+    /// called on.
+    ClassNotFound,
+    /// The function is static and takes a world context, but no `BpContext` is entered. Only
+    /// member functions fall back to the object they are called on.
+    NoWorldContext,
+    /// A status this version of bprust-sys does not know.
+    Unknown(i32),
+}
+
+impl BpCallError {
+    /// Convert the status returned by `process_event` of the function table.
+    pub fn check(status: i32) -> Result<(), Self> {
+        match status {
+            0 => Ok(()),
+            1 => Err(Self::InvalidObject),
+            2 => Err(Self::FunctionNotFound),
+            status => Err(Self::Unknown(status)),
+        }
+    }
+}
+
+impl fmt::Display for BpCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInitialized => {
+                f.write_str("BPRust is not initialized, `LibraryEntry::init` has not been called")
+            }
+            Self::InvalidObject => f.write_str("the object is not valid"),
+            Self::FunctionNotFound => f.write_str("the function is not found on the object"),
+            Self::Unsupported => {
+                f.write_str("the plugin does not provide the function table entries of the call")
+            }
+            Self::ClassNotFound => f.write_str("the class of the static function is not found"),
+            Self::NoWorldContext => f.write_str(
+                "the static function needs a world context, but no `BpContext` is entered",
+            ),
+            Self::Unknown(status) => write!(f, "unknown call status {status}"),
+        }
+    }
+}
+
+impl Error for BpCallError {}
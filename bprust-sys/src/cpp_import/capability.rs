@@ -0,0 +1,60 @@
+use std::{ffi::CStr, marker::PhantomData, ptr};
+
+use crate::{cpp_import::try_cpp_get, lifecycle::GenerationCache};
+
+/// An optional function table the plugin may provide, looked up by name at runtime.
+///
+/// Features built on a capability should check for it and degrade gracefully, so a library
+/// keeps working with plugin versions that don't provide it.
+///
+/// # Safety
+/// `Self` must be `#[repr(C)]` and match the table the plugin registers under `NAME`.
+pub unsafe trait Capability: Sync + 'static {
+    /// Name of the capability, versioned like `BPRust.Log.v1`.
+    const NAME: &'static CStr;
+}
+
+/// Look up a capability, `None` if the plugin doesn't provide it or the function table is not
+/// installed, e.g. before `LibraryEntry::init` or after `LibraryEntry::shutdown`.
+///
+/// The table is valid until `LibraryEntry::shutdown`. Put a [`CapabilityCache`] in a static
+/// to avoid looking it up on every use.
+pub fn capability<C: Capability>() -> Option<&'static C> {
+    let get_capability = try_cpp_get().ok()?.get_capability?;
+    let table = unsafe { get_capability(C::NAME.as_ptr()) };
+    unsafe { (table as *const C).as_ref() }
+}
+
+/// Cache of a capability lookup, dropped when BPRust shuts down.
+pub struct CapabilityCache<C> {
+    table: GenerationCache,
+    _marker: PhantomData<fn() -> C>,
+}
+
+impl<C: Capability> CapabilityCache<C> {
+    pub const fn new() -> Self {
+        Self {
+            table: GenerationCache::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self) -> Option<&'static C> {
+        if let Some(table) = self.table.get() {
+            return unsafe { (table as *const C).as_ref() };
+        }
+
+        // a miss without the function table is not cached, the capability may come with `init`
+        try_cpp_get().ok()?;
+        let table = capability::<C>();
+        self.table
+            .set(table.map_or(ptr::null_mut(), |t| t as *const C as *mut ()));
+        table
+    }
+}
+
+impl<C: Capability> Default for CapabilityCache<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
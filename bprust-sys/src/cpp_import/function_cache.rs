@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeMap,
+    ffi::CStr,
+    sync::{PoisonError, RwLock},
+};
+
+use super::{BpCallError, CppFunctionTable};
+use crate::lifecycle;
+
+/// Resolved `UFunction`s of one function, by the class of the object it is called on, so the
+/// name is only looked up once per class. Generated wrappers keep one in a `static`.
+///
+/// The function is resolved on the class of the object rather than the declaring class, so
+/// Blueprint overrides are called like they are through `ProcessEvent` by name.
+pub struct UFunctionCache {
+    name: &'static CStr,
+    /// Generation of the resolved functions, and `UClass` address to `UFunction` address.
+    functions: RwLock<(u64, BTreeMap<usize, usize>)>,
+}
+
+impl UFunctionCache {
+    pub const fn new(name: &'static CStr) -> Self {
+        Self {
+            name,
+            functions: RwLock::new((0, BTreeMap::new())),
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static CStr {
+        self.name
+    }
+
+    /// Resolve the function on the class of `u_object`, `None` if the function table does not
+    /// provide function lookups.
+    pub(crate) fn get_or_find(
+        &self,
+        table: &CppFunctionTable,
+        u_object: *mut (),
+    ) -> Option<Result<*mut (), BpCallError>> {
+        let (Some(object_get_class), Some(find_function)) =
+            (table.object_get_class, table.find_function)
+        else {
+            return None;
+        };
+
+        let class = unsafe { object_get_class(u_object) };
+        if class.is_null() {
+            return Some(Err(BpCallError::InvalidObject));
+        }
+
+        let generation = lifecycle::generation();
+        {
+            let functions = self
+                .functions
+                .read()
+                .unwrap_or_else(PoisonError::into_inner);
+            if functions.0 == generation
+                && let Some(&function) = functions.1.get(&(class as usize))
+            {
+                return Some(Ok(function as *mut ()));
+            }
+        }
+
+        let function = unsafe { find_function(class, self.name.as_ptr()) };
+        let mut functions = self
+            .functions
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        // classes of a previous initialization may be gone, their addresses may be reused
+        if functions.0 != generation {
+            *functions = (generation, BTreeMap::new());
+        }
+        if function.is_null() {
+            return Some(Err(BpCallError::FunctionNotFound));
+        }
+        functions.1.insert(class as usize, function as usize);
+        Some(Ok(function))
+    }
+}
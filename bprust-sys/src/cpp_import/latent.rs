@@ -0,0 +1,186 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        Mutex, MutexGuard,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, Waker},
+};
+
+use crate::{
+    FName,
+    cpp_import::{BpCallError, ParamFrame, try_cpp_get},
+};
+
+/// Size of `FLatentActionInfo`: `Linkage` and `UUID`, the `ExecutionFunction` name and the weak
+/// `CallbackTarget`. Generated bindings check the exported latent info parameters against it.
+pub const LATENT_ACTION_INFO_SIZE: usize = 4 + 4 + size_of::<FName>() + 8;
+
+enum Pending {
+    Waiting(Option<Waker>),
+    Completed,
+    /// The future was dropped before the action finished. UE may still write the outputs, so
+    /// the frame is kept until then.
+    Orphaned(Box<dyn Send>),
+}
+
+static PENDING: Mutex<BTreeMap<u64, Pending>> = Mutex::new(BTreeMap::new());
+
+static NEXT_ACTION_ID: AtomicU64 = AtomicU64::new(0);
+
+fn pending() -> MutexGuard<'static, BTreeMap<u64, Pending>> {
+    PENDING.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+extern "C" fn on_complete(id: u64) {
+    let mut pending = pending();
+    let waker = match pending.get_mut(&id) {
+        Some(Pending::Waiting(waker)) => {
+            let waker = waker.take();
+            pending.insert(id, Pending::Completed);
+            waker
+        }
+        Some(Pending::Orphaned(_)) => {
+            pending.remove(&id);
+            None
+        }
+        Some(Pending::Completed) | None => None,
+    };
+    drop(pending);
+
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+/// A call of a latent Blueprint function, e.g. `Delay`, completing when UE finishes the latent
+/// action. Awaiting it returns the parameter frame to read the outputs from.
+///
+/// The frame is on the heap, since UE keeps pointers to the outputs until the action finishes.
+/// If the future is dropped before, the frame is kept alive until then and the outputs are
+/// leaked.
+pub struct LatentCall<const SIZE: usize> {
+    id: u64,
+    params: Option<Box<ParamFrame<SIZE>>>,
+    started: bool,
+}
+
+impl<const SIZE: usize> LatentCall<SIZE> {
+    /// Returns `BpCallError::Unsupported` if the plugin does not provide latent actions.
+    pub fn new() -> Result<Self, BpCallError> {
+        let table = try_cpp_get().map_err(|_| BpCallError::NotInitialized)?;
+        if table.latent_action_info.is_none() || table.latent_action_forget.is_none() {
+            return Err(BpCallError::Unsupported);
+        }
+        Ok(Self {
+            id: NEXT_ACTION_ID.fetch_add(1, Ordering::Relaxed),
+            params: Some(Box::new(ParamFrame::new())),
+            started: false,
+        })
+    }
+
+    /// Write the `FLatentActionInfo` completing this call at `info_offset`, then start the
+    /// action by calling the function with `call`.
+    ///
+    /// # Safety
+    /// A `FLatentActionInfo` must fit at `info_offset` of the frame.
+    pub unsafe fn start(
+        &mut self,
+        info_offset: usize,
+        call: impl FnOnce(&mut ParamFrame<SIZE>) -> Result<(), BpCallError>,
+    ) -> Result<(), BpCallError> {
+        let table = try_cpp_get().map_err(|_| BpCallError::NotInitialized)?;
+        let (Some(latent_action_info), Some(latent_action_forget)) =
+            (table.latent_action_info, table.latent_action_forget)
+        else {
+            return Err(BpCallError::Unsupported);
+        };
+        let params = self
+            .params
+            .as_mut()
+            .expect("BPRust ERROR: latent call started twice");
+
+        pending().insert(self.id, Pending::Waiting(None));
+        unsafe {
+            let info = params.as_mut_ptr().cast::<u8>().add(info_offset).cast();
+            latent_action_info(info, self.id, on_complete);
+        }
+
+        match call(params) {
+            Ok(()) => {
+                self.started = true;
+                Ok(())
+            }
+            Err(err) => {
+                pending().remove(&self.id);
+                unsafe { latent_action_forget(self.id, on_complete) };
+                Err(err)
+            }
+        }
+    }
+}
+
+impl<const SIZE: usize> Future for LatentCall<SIZE> {
+    type Output = Box<ParamFrame<SIZE>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut pending = pending();
+        match pending.get_mut(&self.id) {
+            Some(Pending::Completed) => {
+                pending.remove(&self.id);
+                drop(pending);
+                let params = self
+                    .params
+                    .take()
+                    .expect("BPRust ERROR: latent call polled after completion");
+                Poll::Ready(params)
+            }
+            Some(Pending::Waiting(waker)) => {
+                *waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            // not started, or forgotten by shutdown, so it never completes
+            Some(Pending::Orphaned(_)) | None => Poll::Pending,
+        }
+    }
+}
+
+impl<const SIZE: usize> Drop for LatentCall<SIZE> {
+    fn drop(&mut self) {
+        let Some(params) = self.params.take() else {
+            return;
+        };
+        if !self.started {
+            return;
+        }
+        let mut pending = pending();
+        match pending.get(&self.id) {
+            Some(Pending::Waiting(_)) => {
+                pending.insert(self.id, Pending::Orphaned(params));
+            }
+            Some(Pending::Completed) => {
+                pending.remove(&self.id);
+            }
+            Some(Pending::Orphaned(_)) | None => {}
+        }
+    }
+}
+
+/// Make the plugin forget the pending actions, whose callbacks are stale once the library is
+/// unloaded. Frames of orphaned calls are leaked, UE may still write to them.
+pub(crate) fn forget_latent_actions() {
+    let pending = std::mem::take(&mut *pending());
+    let forget = try_cpp_get()
+        .ok()
+        .and_then(|table| table.latent_action_forget);
+    for (id, action) in pending {
+        if let Some(forget) = forget {
+            unsafe { forget(id, on_complete) };
+        }
+        if let Pending::Orphaned(params) = action {
+            std::mem::forget(params);
+        }
+    }
+}
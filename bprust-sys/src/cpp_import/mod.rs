@@ -1,15 +1,50 @@
 use std::{
-    cell::UnsafeCell,
+    error::Error,
     ffi::{CStr, c_char},
+    fmt,
+    marker::PhantomData,
+    mem::{MaybeUninit, offset_of, size_of},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
 };
 
+use crate::{FName, FScriptArray, FScriptMap, FScriptSet, FString, FText, lifecycle};
+
+mod call;
+mod capability;
 pub mod custom_thunk;
+mod function_cache;
+mod latent;
+mod param_frame;
+mod status;
+
+pub use call::BpCallError;
+pub use capability::{Capability, CapabilityCache, capability};
+pub use function_cache::UFunctionCache;
+pub use latent::{LATENT_ACTION_INFO_SIZE, LatentCall};
+pub use param_frame::{ParamFrame, ParamOps};
+pub use status::{BPRustStatus, ffi_guard};
+
+pub(crate) use latent::forget_latent_actions;
+
+/// The installed table. Once installed, a table is never freed, so the references handed
+/// out by `cpp_get` stay valid for the rest of the process.
+///
+/// Every Rust library links its own copy of bprust-sys, so each library loaded by the plugin
+/// has its own table and is initialized and shut down independently.
+static CPP_FUNCTION_TABLE: AtomicPtr<CppFunctionTable> = AtomicPtr::new(ptr::null_mut());
 
-static CPP_FUNCTION_TABLE: InitCell = InitCell(UnsafeCell::new(None));
+/// ABI version of `CppFunctionTable`, the C++ side must pass the same version to [`init`].
+///
+/// Bump it when existing entries are changed, reordered or removed. Appending entries is not
+/// a breaking change: appended entries must be `Option`s, a plugin built against an older
+/// `ffi.h` passes a smaller table and the missing entries are `None`. It is also the
+/// `abi_version` of `LibraryEntry`, which the plugin reads, so any change to it is breaking.
+pub const CPP_FUNCTION_TABLE_VERSION: u32 = 3;
 
-struct InitCell(UnsafeCell<Option<CppFunctionTable>>);
-unsafe impl Send for InitCell {}
-unsafe impl Sync for InitCell {}
+/// Size of the entries every plugin of `CPP_FUNCTION_TABLE_VERSION` provides. Entries after
+/// this point are optional.
+const REQUIRED_TABLE_SIZE: usize = offset_of!(CppFunctionTable, get_capability);
 
 #[repr(C)]
 pub struct CppFunctionTable {
@@ -19,47 +54,327 @@ pub struct CppFunctionTable {
         resolve_param: extern "C" fn(user_data: *mut (), handler: &mut custom_thunk::Handler),
         call_function: extern "C" fn(user_data: *mut (), u_object: *mut ()),
     ),
+    /// Returns a status, see [`BpCallError::check`].
     pub process_event:
-        unsafe extern "C" fn(u_object: *mut (), fn_name: *const c_char, params: *mut ()),
+        unsafe extern "C" fn(u_object: *mut (), fn_name: *const c_char, params: *mut ()) -> i32,
+    pub is_valid: unsafe extern "C" fn(u_object: *mut ()) -> bool,
+    pub find_class: unsafe extern "C" fn(path: *const c_char) -> *mut (),
+    pub object_is_a: unsafe extern "C" fn(u_object: *mut (), class: *mut ()) -> bool,
+
+    pub gc_root_add: unsafe extern "C" fn(u_object: *mut ()) -> u64,
+    pub gc_root_get: unsafe extern "C" fn(handle: u64) -> *mut (),
+    pub gc_root_remove: unsafe extern "C" fn(handle: u64),
+
+    pub text_from_string: unsafe extern "C" fn(out: *mut FText, string: StrView),
+    pub text_as_culture_invariant: unsafe extern "C" fn(out: *mut FText, string: StrView),
+    pub text_find: unsafe extern "C" fn(out: *mut FText, namespace: StrView, key: StrView) -> bool,
+    pub text_format: unsafe extern "C" fn(
+        out: *mut FText,
+        pattern: *const FText,
+        args: *const FormatNamedArgument,
+        args_len: usize,
+    ),
+    pub text_to_string: unsafe extern "C" fn(
+        text: *const FText,
+        user_data: *mut (),
+        write: extern "C" fn(user_data: *mut (), chars: StrView),
+    ),
+    pub text_clone: unsafe extern "C" fn(out: *mut FText, text: *const FText),
+    pub text_drop: unsafe extern "C" fn(text: *mut FText),
+
+    // ---- optional entries ----
+    /// Look up an optional function table by name, see [`Capability`].
+    pub get_capability: Option<unsafe extern "C" fn(name: *const c_char) -> *const ()>,
+    /// `u_object->GetClass()`, null if `u_object` is not valid.
+    pub object_get_class: Option<unsafe extern "C" fn(u_object: *mut ()) -> *mut ()>,
+    /// `u_class->FindFunctionByName(name)`, null if not found.
+    pub find_function:
+        Option<unsafe extern "C" fn(class: *mut (), fn_name: *const c_char) -> *mut ()>,
+    /// `process_event` with a `UFunction` resolved by `find_function`.
+    pub process_event_with_function:
+        Option<unsafe extern "C" fn(u_object: *mut (), function: *mut (), params: *mut ()) -> i32>,
+    /// `InitializeValue_InContainer` of the parameters of `function` at `indices`, see
+    /// [`ParamOps`].
+    pub init_params: Option<
+        unsafe extern "C" fn(function: *mut (), params: *mut (), indices: *const usize, len: usize),
+    >,
+    /// `DestroyValue_InContainer` of the parameters of `function` at `indices`.
+    pub destroy_params: Option<
+        unsafe extern "C" fn(function: *mut (), params: *mut (), indices: *const usize, len: usize),
+    >,
+    /// `FName(name, FNAME_Add)`, `out` is uninitialized.
+    pub name_from_string: Option<unsafe extern "C" fn(out: *mut FName, name: StrView)>,
+    /// Write a `FLatentActionInfo` to the uninitialized `out`, whose callback calls
+    /// `on_complete(id)` once the latent action finishes, see [`LatentCall`].
+    pub latent_action_info:
+        Option<unsafe extern "C" fn(out: *mut (), id: u64, on_complete: extern "C" fn(id: u64))>,
+    /// Forget the callback of the latent action `id` passed with `on_complete`, its action
+    /// won't complete it. Ids are only unique per library, `on_complete` tells them apart.
+    pub latent_action_forget:
+        Option<unsafe extern "C" fn(id: u64, on_complete: extern "C" fn(id: u64))>,
+    /// `string->~FString()`.
+    pub string_drop: Option<unsafe extern "C" fn(string: *mut FString)>,
+    /// `array->~FScriptArray()`, frees the allocation without destroying the elements.
+    pub script_array_drop: Option<unsafe extern "C" fn(array: *mut FScriptArray)>,
+    /// `set->~FScriptSet()`, frees the allocations without destroying the elements.
+    pub script_set_drop: Option<unsafe extern "C" fn(set: *mut FScriptSet)>,
+    /// `map->~FScriptMap()`, frees the allocations without destroying the pairs.
+    pub script_map_drop: Option<unsafe extern "C" fn(map: *mut FScriptMap)>,
+    /// `class->GetDefaultObject()`, which static functions are called on.
+    pub class_default_object: Option<unsafe extern "C" fn(class: *mut ()) -> *mut ()>,
+}
+
+/// A borrowed UTF-8 string passed across the FFI boundary. Not null terminated.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct StrView<'a> {
+    pub ptr: *const u8,
+    pub len: usize,
+    _marker: PhantomData<&'a str>,
+}
+
+impl<'a> StrView<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self {
+            ptr: s.as_ptr(),
+            len: s.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// # Safety
+    /// `ptr` must point to `len` readable bytes that live for `'a`.
+    pub unsafe fn as_bytes(&self) -> &'a [u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<'a> From<&'a str> for StrView<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Value of a named argument of `FText::Format`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub enum FormatArgumentValue<'a> {
+    Int(i64),
+    UInt(u64),
+    Float(f32),
+    Double(f64),
+    Text(&'a FText),
+}
+
+#[repr(C)]
+pub struct FormatNamedArgument<'a> {
+    pub name: StrView<'a>,
+    pub value: FormatArgumentValue<'a>,
 }
 
-#[allow(non_snake_case)]
-#[unsafe(no_mangle)]
-unsafe extern "C" fn BPRustSys_init(table: CppFunctionTable) {
-    unsafe {
-        match &mut *CPP_FUNCTION_TABLE.0.get() {
-            Some(_) => {
-                panic!(
-                    "BPRust ERROR: BPRust is already initialized, don't call `BPRustSys_init` twice"
-                )
+/// Install the function table, called by `LibraryEntry::init` that [`register_library!`] exports.
+///
+/// `version` and `size` are `BPRUST_ABI_VERSION` and `sizeof(CppFunctionTable)` of the
+/// `ffi.h` the plugin is built with. The table is copied, it doesn't need to outlive this call.
+///
+/// # Safety
+/// `table` must be null or point to `size` readable bytes of a `CppFunctionTable`.
+///
+/// [`register_library!`]: crate::register_library
+pub unsafe fn init(table: *const CppFunctionTable, version: u32, size: usize) -> BPRustStatus {
+    ffi_guard(|| {
+        if table.is_null() || version != CPP_FUNCTION_TABLE_VERSION || size < REQUIRED_TABLE_SIZE {
+            return BPRustStatus::IncompatibleTable;
+        }
+
+        // entries the plugin doesn't know are left zeroed, which is `None`
+        let mut copied = Box::new(MaybeUninit::<CppFunctionTable>::zeroed());
+        unsafe {
+            ptr::copy_nonoverlapping(
+                table as *const u8,
+                copied.as_mut_ptr() as *mut u8,
+                size.min(size_of::<CppFunctionTable>()),
+            );
+        }
+        let table = Box::into_raw(copied) as *mut CppFunctionTable;
+
+        let installed = CPP_FUNCTION_TABLE.compare_exchange(
+            ptr::null_mut(),
+            table,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+
+        match installed {
+            Ok(_) => BPRustStatus::Ok,
+            Err(_) => {
+                drop(unsafe { Box::from_raw(table) });
+                BPRustStatus::AlreadyInitialized
             }
-            place @ None => *place = Some(table),
         }
+    })
+}
+
+/// A human readable description of `status` for logging, a static null terminated string.
+pub fn status_message(status: i32) -> *const c_char {
+    BPRustStatus::from_raw(status)
+        .map_or(c"unknown status", BPRustStatus::message)
+        .as_ptr()
+}
+
+/// Run the shutdown hooks, release everything Rust registered with UE and remove the
+/// function table. [`init`] may be called again afterwards.
+///
+/// Must not run concurrently with other calls into the library.
+pub fn shutdown() -> BPRustStatus {
+    ffi_guard(|| {
+        if try_cpp_get().is_err() {
+            return BPRustStatus::NotInitialized;
+        }
+
+        let hooks_succeeded = lifecycle::shutdown();
+        CPP_FUNCTION_TABLE.store(ptr::null_mut(), Ordering::Release);
+
+        if hooks_succeeded {
+            BPRustStatus::Ok
+        } else {
+            BPRustStatus::Panicked
+        }
+    })
+}
+
+/// The function table has not been installed by [`init`] yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotInitializedError;
+
+impl fmt::Display for NotInitializedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BPRust is not initialized, `LibraryEntry::init` has not been called")
+    }
+}
+
+impl Error for NotInitializedError {}
+
+pub fn try_cpp_get() -> Result<&'static CppFunctionTable, NotInitializedError> {
+    let table = CPP_FUNCTION_TABLE.load(Ordering::Acquire);
+    if table.is_null() {
+        Err(NotInitializedError)
+    } else {
+        Ok(unsafe { &*table })
     }
 }
 
 pub fn cpp_get() -> &'static CppFunctionTable {
-    unsafe {
-        let table = &*CPP_FUNCTION_TABLE.0.get();
-        match table {
-            Some(t) => t,
-            None => {
-                panic!("BPRust ERROR: should NOT use any functions before calling `BPRustSys_init`")
-            }
+    match try_cpp_get() {
+        Ok(table) => table,
+        Err(_) => {
+            panic!("BPRust ERROR: should NOT use any functions before calling `LibraryEntry::init`")
         }
     }
 }
 
+/// Call the function named `fn_name` on `u_object` through `ProcessEvent`.
+///
+/// # Safety
+/// `params` must match the parameter layout of the function. The outputs in `params` are
+/// only written if the call succeeds.
 pub unsafe fn process_event<UObject, Param>(
     u_object: &UObject,
     fn_name: &'static CStr,
     params: &mut Param,
-) {
-    unsafe {
-        (cpp_get().process_event)(
+) -> Result<(), BpCallError> {
+    let table = try_cpp_get().map_err(|_| BpCallError::NotInitialized)?;
+    let status = unsafe {
+        (table.process_event)(
             u_object as *const _ as _,
             fn_name.as_ptr(),
             params as *mut _ as _,
         )
+    };
+    BpCallError::check(status)
+}
+
+/// Call the function cached in `function` on `u_object` with the parameters in `params`.
+///
+/// The `UFunction` is resolved once per class instead of by name on every call. `write_inputs`
+/// moves the inputs into `params` once the function is resolved, and the parameters in `ops`
+/// are constructed and destroyed around the call through UE. If `write_inputs` was called,
+/// `copy_back` is called after the call, even if it failed, to move the reference parameters
+/// back to the caller.
+///
+/// # Safety
+/// `params` and `ops` must match the parameters of the function, and `write_inputs` must only
+/// write the inputs. The outputs in `params` are only valid if the call succeeds.
+pub unsafe fn process_event_with_function<UObject, const SIZE: usize>(
+    u_object: &UObject,
+    function: &'static UFunctionCache,
+    params: &mut ParamFrame<SIZE>,
+    ops: &ParamOps,
+    write_inputs: impl FnOnce(&mut ParamFrame<SIZE>),
+    copy_back: impl FnOnce(&mut ParamFrame<SIZE>),
+) -> Result<(), BpCallError> {
+    let table = try_cpp_get().map_err(|_| BpCallError::NotInitialized)?;
+    let u_object = u_object as *const _ as *mut ();
+
+    let (Some(process_event_with_function), Some(resolved)) = (
+        table.process_event_with_function,
+        function.get_or_find(table, u_object),
+    ) else {
+        // the plugin does not resolve functions, so the parameters cannot be constructed
+        if !ops.is_empty() {
+            return Err(BpCallError::Unsupported);
+        }
+        write_inputs(params);
+        let status = unsafe {
+            (table.process_event)(u_object, function.name().as_ptr(), params.as_mut_ptr())
+        };
+        copy_back(params);
+        return BpCallError::check(status);
+    };
+    let u_function = resolved?;
+
+    let param_ops = if ops.is_empty() {
+        None
+    } else {
+        match (table.init_params, table.destroy_params) {
+            (Some(init_params), Some(destroy_params)) => Some((init_params, destroy_params)),
+            _ => return Err(BpCallError::Unsupported),
+        }
+    };
+
+    if let Some((init_params, _)) = param_ops
+        && !ops.init.is_empty()
+    {
+        unsafe {
+            init_params(
+                u_function,
+                params.as_mut_ptr(),
+                ops.init.as_ptr(),
+                ops.init.len(),
+            )
+        }
+    }
+    write_inputs(params);
+
+    let frame = params.as_mut_ptr();
+    let result =
+        BpCallError::check(unsafe { process_event_with_function(u_object, u_function, frame) });
+
+    if let Some((_, destroy_params)) = param_ops {
+        let destroy = |indices: &[usize]| {
+            if !indices.is_empty() {
+                unsafe { destroy_params(u_function, frame, indices.as_ptr(), indices.len()) }
+            }
+        };
+        destroy(ops.destroy);
+        // the outputs are only moved out of the frame if the call succeeds
+        if result.is_err() {
+            destroy(ops.init);
+        }
     }
+    copy_back(params);
+    result
 }
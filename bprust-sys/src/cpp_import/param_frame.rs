@@ -0,0 +1,69 @@
+use std::{mem::MaybeUninit, ptr};
+
+/// Parameter memory of a `UFunction`, `SIZE` is its `ParmsSize`. Generated wrappers access the
+/// parameters by the offsets exported from the `UFunction`, so the layout matches UE even
+/// where it differs from a `#[repr(C)]` struct.
+///
+/// Starts zeroed like the parameters `ProcessEvent` allocates for Blueprint calls, and is
+/// aligned for every parameter type UE supports.
+#[repr(C, align(16))]
+pub struct ParamFrame<const SIZE: usize> {
+    bytes: [MaybeUninit<u8>; SIZE],
+}
+
+impl<const SIZE: usize> ParamFrame<SIZE> {
+    pub fn as_mut_ptr(&mut self) -> *mut () {
+        self.bytes.as_mut_ptr().cast()
+    }
+
+    pub fn new() -> Self {
+        Self {
+            bytes: [MaybeUninit::zeroed(); SIZE],
+        }
+    }
+
+    /// # Safety
+    /// A `T` must fit at `offset`, which the generated layout asserts check at compile time.
+    /// The previous value at `offset` is overwritten without being dropped.
+    pub unsafe fn write<T>(&mut self, offset: usize, value: T) {
+        unsafe { ptr::write(self.bytes.as_mut_ptr().add(offset).cast::<T>(), value) }
+    }
+
+    /// Move the value at `offset` out of the frame.
+    ///
+    /// # Safety
+    /// Same as [`ParamFrame::write`], and the memory at `offset` must hold a valid `T` that is
+    /// not read again.
+    pub unsafe fn read<T>(&self, offset: usize) -> T {
+        unsafe { ptr::read(self.bytes.as_ptr().add(offset).cast::<T>()) }
+    }
+}
+
+impl<const SIZE: usize> Default for ParamFrame<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parameters UE has to construct or destroy in a [`ParamFrame`], as indices of the parameters
+/// in declaration order. Parameters whose properties are `ZeroConstructor` and `NoDestructor`
+/// are left out, the zeroed frame is enough for them.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamOps {
+    /// Outputs to construct before the call. Destroyed if the call fails, otherwise they are
+    /// moved out of the frame.
+    pub init: &'static [usize],
+    /// Inputs moved into the frame, destroyed after the call.
+    pub destroy: &'static [usize],
+}
+
+impl ParamOps {
+    pub const NONE: Self = Self {
+        init: &[],
+        destroy: &[],
+    };
+
+    pub fn is_empty(&self) -> bool {
+        self.init.is_empty() && self.destroy.is_empty()
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    ffi::CStr,
+    fmt,
+    panic::{AssertUnwindSafe, catch_unwind},
+};
+
+/// Status returned by every `extern "C"` entry point exported to C++.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub enum BPRustStatus {
+    Ok = 0,
+    /// `LibraryEntry::init` was called while a function table is already installed.
+    AlreadyInitialized = 1,
+    /// An entry point that needs the function table was called before `LibraryEntry::init`.
+    NotInitialized = 2,
+    /// Rust code panicked. The panic message has been reported by the panic hook.
+    Panicked = 3,
+    /// The function table passed to `LibraryEntry::init` has a different ABI version, or is
+    /// smaller than the entries this version requires.
+    IncompatibleTable = 4,
+}
+
+impl BPRustStatus {
+    pub fn is_ok(self) -> bool {
+        self == Self::Ok
+    }
+
+    pub fn from_raw(status: i32) -> Option<Self> {
+        Some(match status {
+            0 => Self::Ok,
+            1 => Self::AlreadyInitialized,
+            2 => Self::NotInitialized,
+            3 => Self::Panicked,
+            4 => Self::IncompatibleTable,
+            _ => return None,
+        })
+    }
+
+    pub fn message(self) -> &'static CStr {
+        match self {
+            Self::Ok => c"ok",
+            Self::AlreadyInitialized => c"BPRust is already initialized",
+            Self::NotInitialized => c"BPRust is not initialized",
+            Self::Panicked => c"Rust code panicked",
+            Self::IncompatibleTable => {
+                c"the function table is incompatible, rebuild the Rust library against the `ffi.h` of the plugin"
+            }
+        }
+    }
+}
+
+impl fmt::Display for BPRustStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message().to_string_lossy())
+    }
+}
+
+/// Run the body of an `extern "C"` function exported to C++, so that a panic is reported
+/// as [`BPRustStatus::Panicked`] instead of unwinding into C++ and aborting the process.
+///
+/// Panics can only be caught when the library is built with `panic = "unwind"`.
+pub fn ffi_guard(f: impl FnOnce() -> BPRustStatus) -> BPRustStatus {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(BPRustStatus::Panicked)
+}
@@ -0,0 +1,88 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+type Task = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    /// Tasks live on the game thread, which ticks them, so they don't have to be `Send`.
+    static TASKS: RefCell<BTreeMap<u64, Task>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// Tasks to poll on the next tick. Wakers may be called from any thread.
+static READY: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(0);
+
+struct TaskWaker(u64);
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        ready().insert(self.0);
+    }
+}
+
+fn ready() -> std::sync::MutexGuard<'static, BTreeSet<u64>> {
+    READY.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Run `future` on the executor driven by the engine tick, e.g. a gameplay sequence awaiting
+/// latent Blueprint functions. It is first polled on the next tick, and afterwards on the first
+/// tick after it is woken.
+///
+/// Must be called on the game thread. Tasks are dropped by `LibraryEntry::shutdown`.
+pub fn spawn(future: impl Future<Output = ()> + 'static) {
+    let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+    TASKS.with_borrow_mut(|tasks| tasks.insert(id, Box::pin(future)));
+    ready().insert(id);
+}
+
+/// Poll the tasks woken since the last tick. Tasks woken while polling are polled on the next
+/// tick. A task that panics is dropped, returns false if any did.
+pub(crate) fn tick() -> bool {
+    let woken = std::mem::take(&mut *ready());
+
+    let mut all_succeeded = true;
+    for id in woken {
+        // taken out of the map while polling, so that the task can spawn other tasks
+        let Some(mut task) = TASKS.with_borrow_mut(|tasks| tasks.remove(&id)) else {
+            continue;
+        };
+
+        let waker = Waker::from(Arc::new(TaskWaker(id)));
+        let polled = catch_unwind(AssertUnwindSafe(|| {
+            task.as_mut().poll(&mut Context::from_waker(&waker))
+        }));
+
+        match polled {
+            Ok(Poll::Pending) => {
+                TASKS.with_borrow_mut(|tasks| tasks.insert(id, task));
+            }
+            Ok(Poll::Ready(())) => {}
+            Err(_) => all_succeeded = false,
+        }
+    }
+    all_succeeded
+}
+
+/// Drop every task, called on the game thread by shutdown.
+pub(crate) fn clear() {
+    let tasks = TASKS.with_borrow_mut(std::mem::take);
+    // dropped outside the borrow, a task may spawn in its destructor
+    drop(tasks);
+    TASKS.with_borrow_mut(BTreeMap::clear);
+    ready().clear();
+}
@@ -0,0 +1,146 @@
+use std::{
+    any::type_name,
+    collections::BTreeMap,
+    fmt,
+    marker::PhantomData,
+    panic::Location,
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{UObjectRef, cpp_import::cpp_get, lifecycle};
+
+/// Roots of the current generation, keyed by handle.
+static GC_ROOTS: Mutex<BTreeMap<u64, RootRecord>> = Mutex::new(BTreeMap::new());
+
+struct RootRecord {
+    type_name: &'static str,
+    location: &'static Location<'static>,
+}
+
+fn gc_roots() -> MutexGuard<'static, BTreeMap<u64, RootRecord>> {
+    // the map is always left consistent, so a poisoned lock is still usable
+    GC_ROOTS.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// A strong reference keeping a UObject from being garbage collected.
+///
+/// The object is registered to a `FGCObject` collector on the C++ side and released on drop.
+/// It can still be destroyed explicitly (e.g. `DestroyActor`), in which case
+/// [`UStrong::get`] returns `None`.
+///
+/// All roots are released when BPRust shuts down, after which `get` returns `None` as well.
+pub struct UStrong<T> {
+    handle: u64,
+    generation: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> UStrong<T> {
+    /// # Safety
+    /// The object must be valid, see [`UObjectRef::is_valid`].
+    #[track_caller]
+    pub unsafe fn new(object: UObjectRef<T>) -> Self {
+        unsafe { Self::add_root(object.as_ptr()) }
+    }
+
+    /// The rooted object, `None` if it was destroyed explicitly.
+    pub fn get(&self) -> Option<UObjectRef<T>> {
+        unsafe { UObjectRef::from_raw(self.object_ptr()) }
+    }
+
+    #[track_caller]
+    unsafe fn add_root(object: *mut ()) -> Self {
+        let handle = unsafe { (cpp_get().gc_root_add)(object) };
+        gc_roots().insert(
+            handle,
+            RootRecord {
+                type_name: type_name::<T>(),
+                location: Location::caller(),
+            },
+        );
+        Self {
+            handle,
+            generation: lifecycle::generation(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn is_released(&self) -> bool {
+        self.generation != lifecycle::generation()
+    }
+
+    fn object_ptr(&self) -> *mut () {
+        if self.is_released() {
+            return std::ptr::null_mut();
+        }
+        unsafe { (cpp_get().gc_root_get)(self.handle) }
+    }
+}
+
+impl<T> Clone for UStrong<T> {
+    #[track_caller]
+    fn clone(&self) -> Self {
+        unsafe { Self::add_root(self.object_ptr()) }
+    }
+}
+
+impl<T> Drop for UStrong<T> {
+    fn drop(&mut self) {
+        if self.is_released() {
+            return;
+        }
+        gc_roots().remove(&self.handle);
+        unsafe { (cpp_get().gc_root_remove)(self.handle) }
+    }
+}
+
+impl<T> fmt::Debug for UStrong<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UStrong")
+            .field("handle", &self.handle)
+            .field("object", &self.get())
+            .finish()
+    }
+}
+
+/// A live GC root held by Rust, see [`live_gc_roots`].
+#[derive(Debug)]
+pub struct GcRootInfo {
+    pub handle: u64,
+    pub type_name: &'static str,
+    /// Where the root was created.
+    pub location: &'static Location<'static>,
+    /// The rooted object, null if it was destroyed explicitly.
+    pub object: *mut (),
+}
+
+impl fmt::Display for GcRootInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} ({:p}) created at {}",
+            self.handle, self.type_name, self.object, self.location
+        )
+    }
+}
+
+/// Report all UObjects currently rooted by Rust, ordered by creation.
+pub fn live_gc_roots() -> Vec<GcRootInfo> {
+    gc_roots()
+        .iter()
+        .map(|(&handle, record)| GcRootInfo {
+            handle,
+            type_name: record.type_name,
+            location: record.location,
+            object: unsafe { (cpp_get().gc_root_get)(handle) },
+        })
+        .collect()
+}
+
+/// Release every root held by Rust, called on shutdown.
+pub(crate) fn release_all() {
+    let roots = std::mem::take(&mut *gc_roots());
+    for handle in roots.into_keys() {
+        unsafe { (cpp_get().gc_root_remove)(handle) }
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    collections::BTreeMap,
+    ffi::CStr,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::Mutex,
+};
+
+use crate::{
+    cpp_import::{Capability, CapabilityCache, StrView},
+    library,
+};
+
+/// Storage that outlives an unloaded library, provided by the plugin as capability
+/// `BPRust.HotReload.v1`. Without it, state persisted for hot reload is dropped.
+#[repr(C)]
+pub struct HotReloadCapability {
+    /// Store a copy of `data` under `key`, replacing the previous data.
+    pub store: unsafe extern "C" fn(key: StrView, data: *const u8, len: usize),
+    /// Pass the data stored under `key` to `write` and remove it. Returns false if nothing
+    /// is stored.
+    pub take: unsafe extern "C" fn(
+        key: StrView,
+        user_data: *mut (),
+        write: extern "C" fn(user_data: *mut (), data: *const u8, len: usize),
+    ) -> bool,
+}
+
+unsafe impl Capability for HotReloadCapability {
+    const NAME: &'static CStr = c"BPRust.HotReload.v1";
+}
+
+static HOT_RELOAD: CapabilityCache<HotReloadCapability> = CapabilityCache::new();
+
+type SaveState = Box<dyn FnMut() -> Vec<u8> + Send>;
+
+static SAVERS: Mutex<BTreeMap<&'static str, SaveState>> = Mutex::new(BTreeMap::new());
+
+/// Serialize state with `save` before the library is unloaded for a hot reload, so the
+/// reloaded library can restore it with [`take_reload_state`].
+///
+/// Like every registration, savers are dropped on shutdown, so register them in the startup
+/// function of the library. `key` only needs to be unique within this library.
+pub fn persist_on_reload(key: &'static str, save: impl FnMut() -> Vec<u8> + Send + 'static) {
+    SAVERS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .insert(key, Box::new(save));
+}
+
+/// Take the state saved under `key` by the library instance before the hot reload.
+pub fn take_reload_state(key: &str) -> Option<Vec<u8>> {
+    extern "C" fn write(user_data: *mut (), data: *const u8, len: usize) {
+        let out = unsafe { &mut *(user_data as *mut Vec<u8>) };
+        if len != 0 {
+            out.extend_from_slice(unsafe { std::slice::from_raw_parts(data, len) });
+        }
+    }
+
+    let capability = HOT_RELOAD.get()?;
+    let key = scoped_key(key);
+    let mut out = Vec::new();
+    unsafe {
+        (capability.take)(
+            key.as_str().into(),
+            &mut out as *mut Vec<u8> as *mut (),
+            write,
+        )
+    }
+    .then_some(out)
+}
+
+/// Run every saver and hand the data to the plugin. Returns false if any saver panicked,
+/// the remaining savers still run.
+pub(crate) fn save_all() -> bool {
+    let mut savers = SAVERS.lock().unwrap_or_else(|err| err.into_inner());
+    let capability = HOT_RELOAD.get();
+
+    let mut all_succeeded = true;
+    for (key, save) in savers.iter_mut() {
+        let Ok(data) = catch_unwind(AssertUnwindSafe(save)) else {
+            all_succeeded = false;
+            continue;
+        };
+        if let Some(capability) = capability {
+            let key = scoped_key(key);
+            unsafe { (capability.store)(key.as_str().into(), data.as_ptr(), data.len()) }
+        }
+    }
+    all_succeeded
+}
+
+pub(crate) fn clear() {
+    SAVERS.lock().unwrap_or_else(|err| err.into_inner()).clear();
+}
+
+/// Keys are stored by the plugin for every library, scope them by the library name.
+fn scoped_key(key: &str) -> String {
+    let library = library::library_name().map_or("".into(), CStr::to_string_lossy);
+    format!("{library}/{key}")
+}
@@ -1,4 +1,27 @@
+mod class;
+mod container;
+mod context;
 pub mod cpp_import;
+mod executor;
+mod gc_root;
+mod hot_reload;
+mod library;
+mod lifecycle;
+mod log;
+mod name;
+mod object;
+mod text;
+
+pub use class::{StaticClassCache, SubclassOf, UClassInfo};
+pub use context::BpContext;
+pub use cpp_import::BpCallError;
+pub use executor::spawn;
+pub use gc_root::{GcRootInfo, UStrong, live_gc_roots};
+pub use hot_reload::{HotReloadCapability, persist_on_reload, take_reload_state};
+pub use library::{LibraryEntry, init_library, prepare_reload, tick};
+pub use lifecycle::on_shutdown;
+pub use log::{LogCapability, LogVerbosity, log};
+pub use object::UObjectRef;
 
 fn main() {
     println!("Hello, world!");
@@ -0,0 +1,207 @@
+use std::{
+    ffi::{CStr, c_char},
+    sync::OnceLock,
+};
+
+use crate::{
+    cpp_import::{self, BPRustStatus, CppFunctionTable, ffi_guard},
+    executor, hot_reload,
+};
+
+static LIBRARY_NAME: OnceLock<&'static CStr> = OnceLock::new();
+
+/// Name of this library, set on initialization.
+pub(crate) fn library_name() -> Option<&'static CStr> {
+    LIBRARY_NAME.get().copied()
+}
+
+/// Metadata and entry points of a Rust library, returned by the function
+/// `BPRustSys_<name>_entry` that [`register_library!`] exports.
+///
+/// `abi_version` comes first so the plugin can check it before reading anything else.
+///
+/// [`register_library!`]: crate::register_library
+#[repr(C)]
+pub struct LibraryEntry {
+    pub abi_version: u32,
+    /// Null terminated library name, the crate name by default.
+    pub name: *const c_char,
+    /// Null terminated crate version.
+    pub version: *const c_char,
+    /// Hash of the definitions the bindings are generated from, 0 if not provided.
+    pub definitions_hash: u64,
+    /// Install the function table and run the startup function of the library.
+    /// Called as `init(&Table, BPRUST_ABI_VERSION, sizeof(CppFunctionTable))`.
+    pub init: unsafe extern "C" fn(
+        table: *const CppFunctionTable,
+        version: u32,
+        size: usize,
+    ) -> BPRustStatus,
+    /// See [`cpp_import::shutdown`].
+    pub shutdown: extern "C" fn() -> BPRustStatus,
+    /// See [`cpp_import::status_message`].
+    pub status_message: extern "C" fn(status: i32) -> *const c_char,
+    /// Save the state persisted with [`persist_on_reload`] before the library is shut down
+    /// and unloaded for a hot reload.
+    ///
+    /// [`persist_on_reload`]: crate::persist_on_reload
+    pub prepare_reload: extern "C" fn() -> BPRustStatus,
+    /// Poll the tasks of [`spawn`], called by the plugin on the game thread every engine tick.
+    ///
+    /// [`spawn`]: crate::spawn
+    pub tick: extern "C" fn() -> BPRustStatus,
+}
+
+unsafe impl Sync for LibraryEntry {}
+
+/// Install the function table, then run `startup`.
+///
+/// # Safety
+/// Same as [`cpp_import::init`].
+pub unsafe fn init_library(
+    name: &'static CStr,
+    table: *const CppFunctionTable,
+    version: u32,
+    size: usize,
+    startup: Option<fn()>,
+) -> BPRustStatus {
+    LIBRARY_NAME.get_or_init(|| name);
+    let status = unsafe { cpp_import::init(table, version, size) };
+    match startup {
+        Some(startup) if status.is_ok() => ffi_guard(|| {
+            startup();
+            BPRustStatus::Ok
+        }),
+        _ => status,
+    }
+}
+
+/// Save the state persisted for hot reload, see [`LibraryEntry::prepare_reload`].
+pub fn prepare_reload() -> BPRustStatus {
+    ffi_guard(|| {
+        if cpp_import::try_cpp_get().is_err() {
+            return BPRustStatus::NotInitialized;
+        }
+        if hot_reload::save_all() {
+            BPRustStatus::Ok
+        } else {
+            BPRustStatus::Panicked
+        }
+    })
+}
+
+/// Poll the woken tasks, see [`LibraryEntry::tick`]. Returns `Panicked` if a task panicked,
+/// the other tasks are still polled.
+pub fn tick() -> BPRustStatus {
+    ffi_guard(|| {
+        if cpp_import::try_cpp_get().is_err() {
+            return BPRustStatus::NotInitialized;
+        }
+        if executor::tick() {
+            BPRustStatus::Ok
+        } else {
+            BPRustStatus::Panicked
+        }
+    })
+}
+
+/// Export the entry point of this library, `BPRustSys_<name>_entry`, which returns its
+/// [`LibraryEntry`]. The plugin loads every library in its library directory and finds the
+/// entry point by the file name, so several Rust libraries can be loaded side by side.
+///
+/// ```ignore
+/// bprust_sys::register_library! {
+///     startup: on_startup,
+///     definitions_hash: bp::BPRUST_DEFINITIONS_HASH,
+/// }
+/// ```
+///
+/// Every key is optional:
+///
+/// - `name`: a string literal. The crate name by default, which is the file name of the
+///   cdylib without the `lib` prefix and extension. Only set it if the file is renamed.
+/// - `startup`: a `fn()` run once the function table is installed, on every initialization.
+/// - `definitions_hash`: the `BPRUST_DEFINITIONS_HASH` of the generated bindings.
+#[macro_export]
+macro_rules! register_library {
+    (@parse $name:tt $startup:tt $hash:tt) => {
+        $crate::register_library!(@export $name $startup $hash);
+    };
+    (@parse $name:tt $startup:tt $hash:tt name: $value:literal $(, $($rest:tt)*)?) => {
+        $crate::register_library!(@parse [$value] $startup $hash $($($rest)*)?);
+    };
+    (@parse $name:tt $startup:tt $hash:tt startup: $value:path $(, $($rest:tt)*)?) => {
+        $crate::register_library!(@parse $name [$value] $hash $($($rest)*)?);
+    };
+    (@parse $name:tt $startup:tt $hash:tt definitions_hash: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::register_library!(@parse $name $startup [$value] $($($rest)*)?);
+    };
+    (@export [$($name:tt)*] [$($startup:path)?] [$($hash:expr)?]) => {
+        const _: () = {
+            const NAME: &::core::ffi::CStr =
+                match ::core::ffi::CStr::from_bytes_with_nul(concat!($($name)*, "\0").as_bytes()) {
+                    ::core::result::Result::Ok(name) => name,
+                    ::core::result::Result::Err(_) => panic!("library name contains a nul byte"),
+                };
+
+            unsafe extern "C" fn init(
+                table: *const $crate::cpp_import::CppFunctionTable,
+                version: u32,
+                size: usize,
+            ) -> $crate::cpp_import::BPRustStatus {
+                let startup: ::core::option::Option<fn()> =
+                    $crate::register_library!(@option $($startup)?);
+                unsafe { $crate::init_library(NAME, table, version, size, startup) }
+            }
+
+            extern "C" fn shutdown() -> $crate::cpp_import::BPRustStatus {
+                $crate::cpp_import::shutdown()
+            }
+
+            extern "C" fn status_message(status: i32) -> *const ::core::ffi::c_char {
+                $crate::cpp_import::status_message(status)
+            }
+
+            extern "C" fn prepare_reload() -> $crate::cpp_import::BPRustStatus {
+                $crate::prepare_reload()
+            }
+
+            extern "C" fn tick() -> $crate::cpp_import::BPRustStatus {
+                $crate::tick()
+            }
+
+            static ENTRY: $crate::LibraryEntry = $crate::LibraryEntry {
+                abi_version: $crate::cpp_import::CPP_FUNCTION_TABLE_VERSION,
+                name: NAME.as_ptr(),
+                version: concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr()
+                    as *const ::core::ffi::c_char,
+                definitions_hash: $crate::register_library!(@hash $($hash)?),
+                init,
+                shutdown,
+                status_message,
+                prepare_reload,
+                tick,
+            };
+
+            #[unsafe(export_name = concat!("BPRustSys_", $($name)*, "_entry"))]
+            extern "C" fn entry() -> *const $crate::LibraryEntry {
+                &ENTRY
+            }
+        };
+    };
+    (@option) => {
+        ::core::option::Option::None
+    };
+    (@option $value:expr) => {
+        ::core::option::Option::Some($value)
+    };
+    (@hash) => {
+        0
+    };
+    (@hash $value:expr) => {
+        $value
+    };
+    ($($rest:tt)*) => {
+        $crate::register_library!(@parse [env!("CARGO_CRATE_NAME")] [] [] $($rest)*);
+    };
+}
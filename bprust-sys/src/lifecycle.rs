@@ -0,0 +1,80 @@
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    ptr,
+    sync::{
+        Mutex,
+        atomic::{AtomicPtr, AtomicU64, Ordering},
+    },
+};
+
+use crate::{cpp_import, executor, gc_root, hot_reload};
+
+type ShutdownHook = Box<dyn FnOnce() + Send>;
+
+static SHUTDOWN_HOOKS: Mutex<Vec<ShutdownHook>> = Mutex::new(Vec::new());
+
+/// Increased by every shutdown. State bound to UE (GC roots, cached classes) remembers
+/// the generation it was created in and is ignored once it's outdated.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+/// A pointer looked up from UE, forgotten when BPRust shuts down.
+pub(crate) struct GenerationCache {
+    value: AtomicPtr<()>,
+    /// generation + 1 of the stored value, 0 if nothing is stored yet
+    stored: AtomicU64,
+}
+
+impl GenerationCache {
+    pub const fn new() -> Self {
+        Self {
+            value: AtomicPtr::new(ptr::null_mut()),
+            stored: AtomicU64::new(0),
+        }
+    }
+
+    /// The value stored in the current generation, which may be null.
+    pub fn get(&self) -> Option<*mut ()> {
+        // the value is stored before the generation, so read them in the opposite order
+        (self.stored.load(Ordering::Acquire) == generation() + 1)
+            .then(|| self.value.load(Ordering::Acquire))
+    }
+
+    pub fn set(&self, value: *mut ()) {
+        self.value.store(value, Ordering::Release);
+        self.stored.store(generation() + 1, Ordering::Release);
+    }
+}
+
+/// Register a hook run by `LibraryEntry::shutdown` while the function table is still installed.
+///
+/// Hooks run once, in reverse order of registration. A library that is initialized again
+/// must register its hooks again.
+pub fn on_shutdown(hook: impl FnOnce() + Send + 'static) {
+    SHUTDOWN_HOOKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .push(Box::new(hook));
+}
+
+/// Run the shutdown hooks and unbind everything Rust registered with UE.
+/// Returns false if any hook panicked, the remaining hooks still run.
+pub(crate) fn shutdown() -> bool {
+    let hooks = std::mem::take(&mut *SHUTDOWN_HOOKS.lock().unwrap_or_else(|err| err.into_inner()));
+
+    let mut all_succeeded = true;
+    for hook in hooks.into_iter().rev() {
+        all_succeeded &= catch_unwind(AssertUnwindSafe(hook)).is_ok();
+    }
+
+    // dropping the tasks may orphan latent calls, so they are forgotten afterwards
+    executor::clear();
+    cpp_import::forget_latent_actions();
+    gc_root::release_all();
+    hot_reload::clear();
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    all_succeeded
+}
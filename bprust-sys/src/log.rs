@@ -0,0 +1,36 @@
+use std::ffi::CStr;
+
+use crate::cpp_import::{Capability, CapabilityCache, StrView};
+
+/// Verbosity of a log message, same as `ELogVerbosity`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogVerbosity {
+    Fatal = 1,
+    Error = 2,
+    Warning = 3,
+    Display = 4,
+    Log = 5,
+    Verbose = 6,
+    VeryVerbose = 7,
+}
+
+/// Logging through `UE_LOG`, provided by the plugin as capability `BPRust.Log.v1`.
+#[repr(C)]
+pub struct LogCapability {
+    pub log: unsafe extern "C" fn(verbosity: LogVerbosity, message: StrView),
+}
+
+unsafe impl Capability for LogCapability {
+    const NAME: &'static CStr = c"BPRust.Log.v1";
+}
+
+static LOG: CapabilityCache<LogCapability> = CapabilityCache::new();
+
+/// Write a message to the UE log, or to stderr if the plugin doesn't provide logging.
+pub fn log(verbosity: LogVerbosity, message: &str) {
+    match LOG.get() {
+        Some(capability) => unsafe { (capability.log)(verbosity, message.into()) },
+        None => eprintln!("BPRust {verbosity:?}: {message}"),
+    }
+}
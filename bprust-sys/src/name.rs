@@ -0,0 +1,20 @@
+use std::mem::MaybeUninit;
+
+use crate::{FName, cpp_import::cpp_get};
+
+impl FName {
+    /// `NAME_None`.
+    pub const NONE: Self = Self([0; 12]);
+
+    /// Find or add a name, like `FName(TEXT("..."))`.
+    pub fn new(name: &str) -> Self {
+        let Some(name_from_string) = cpp_get().name_from_string else {
+            panic!("BPRust ERROR: the plugin does not support creating `FName`s")
+        };
+        let mut out = MaybeUninit::<Self>::uninit();
+        unsafe {
+            name_from_string(out.as_mut_ptr(), name.into());
+            out.assume_init()
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ptr::NonNull,
+};
+
+use crate::cpp_import::cpp_get;
+
+/// A handle to a UObject owned by the garbage collector.
+///
+/// The garbage collector may destroy the object at any time between two engine ticks,
+/// so a `UObjectRef` does not borrow the object. It holds no weak identity either, a collected
+/// object is only detected by [`UObjectRef::is_valid`] as long as its memory is not reused, so
+/// accessing the object is unsafe.
+///
+/// Never null, `Option<UObjectRef<T>>` has the layout of a nullable `T*`.
+#[repr(transparent)]
+pub struct UObjectRef<T> {
+    ptr: NonNull<()>,
+    _marker: PhantomData<*const T>,
+}
+
+impl<T> UObjectRef<T> {
+    /// # Safety
+    /// `ptr` must be null or point to a UObject whose class is `T` or derived from `T`.
+    pub unsafe fn from_raw(ptr: *mut ()) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| Self {
+            ptr,
+            _marker: PhantomData,
+        })
+    }
+
+    /// # Safety
+    /// `object` must be a UObject whose class is `T` or derived from `T`.
+    pub unsafe fn from_ref(object: &T) -> Self {
+        Self {
+            ptr: NonNull::from(object).cast(),
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn as_ptr(self) -> *mut () {
+        self.ptr.as_ptr()
+    }
+
+    /// Whether the object is alive and not pending kill, same as `IsValid` in C++.
+    ///
+    /// # Safety
+    /// The check reads the object header, so the memory of the object must not have been freed,
+    /// e.g. the handle was obtained within the current tick, or the object is kept alive by a
+    /// [`UStrong`](crate::UStrong) or another reference.
+    pub unsafe fn is_valid(self) -> bool {
+        unsafe { (cpp_get().is_valid)(self.as_ptr()) }
+    }
+
+    /// Access the object if it is still valid.
+    ///
+    /// The returned reference must not be held across a garbage collection.
+    ///
+    /// # Safety
+    /// Same as [`UObjectRef::is_valid`].
+    pub unsafe fn get(&self) -> Option<&T> {
+        unsafe { self.is_valid().then(|| self.get_unchecked()) }
+    }
+
+    /// # Safety
+    /// The object must be valid, see [`UObjectRef::is_valid`].
+    pub unsafe fn get_unchecked(&self) -> &T {
+        unsafe { self.ptr.cast().as_ref() }
+    }
+}
+
+impl<T> Clone for UObjectRef<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for UObjectRef<T> {}
+
+impl<T> PartialEq for UObjectRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr
+    }
+}
+
+impl<T> Eq for UObjectRef<T> {}
+
+impl<T> Hash for UObjectRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.ptr.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for UObjectRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("UObjectRef").field(&self.ptr).finish()
+    }
+}
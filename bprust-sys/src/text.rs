@@ -0,0 +1,147 @@
+use std::{
+    fmt::{self, Write},
+    mem::MaybeUninit,
+};
+
+use crate::{
+    FText,
+    cpp_import::{FormatArgumentValue, FormatNamedArgument, StrView, cpp_get, try_cpp_get},
+};
+
+impl FText {
+    /// Create a text that is gathered for localization like any other source text.
+    pub fn from_string(string: &str) -> Self {
+        unsafe { Self::construct(|out| (cpp_get().text_from_string)(out, string.into())) }
+    }
+
+    /// Create a text that is the same in every culture and is never localized.
+    pub fn as_culture_invariant(string: &str) -> Self {
+        unsafe { Self::construct(|out| (cpp_get().text_as_culture_invariant)(out, string.into())) }
+    }
+
+    pub fn empty() -> Self {
+        Self::as_culture_invariant("")
+    }
+
+    /// Look up a localized text by namespace and key in the string table of the current culture.
+    pub fn find(namespace: &str, key: &str) -> Option<Self> {
+        let mut out = MaybeUninit::<Self>::uninit();
+        unsafe {
+            (cpp_get().text_find)(out.as_mut_ptr(), namespace.into(), key.into())
+                .then(|| out.assume_init())
+        }
+    }
+
+    /// Format `pattern` with named arguments, like `FText::Format(Pattern, FFormatNamedArguments)`.
+    ///
+    /// ```ignore
+    /// let text = FText::format(&pattern, [("Count", 3.into()), ("Name", (&name).into())]);
+    /// ```
+    pub fn format<'a>(
+        pattern: &FText,
+        args: impl IntoIterator<Item = (&'a str, FormatArgumentValue<'a>)>,
+    ) -> Self {
+        let args: Vec<FormatNamedArgument> = args
+            .into_iter()
+            .map(|(name, value)| FormatNamedArgument {
+                name: name.into(),
+                value,
+            })
+            .collect();
+
+        unsafe {
+            Self::construct(|out| (cpp_get().text_format)(out, pattern, args.as_ptr(), args.len()))
+        }
+    }
+
+    unsafe fn construct(f: impl FnOnce(*mut Self)) -> Self {
+        let mut out = MaybeUninit::<Self>::uninit();
+        f(out.as_mut_ptr());
+        unsafe { out.assume_init() }
+    }
+}
+
+impl Clone for FText {
+    fn clone(&self) -> Self {
+        unsafe { Self::construct(|out| (cpp_get().text_clone)(out, self)) }
+    }
+}
+
+impl Drop for FText {
+    fn drop(&mut self) {
+        // without a table the text can't be destroyed, it is leaked instead of panicking in drop
+        if let Ok(table) = try_cpp_get() {
+            unsafe { (table.text_drop)(self) }
+        }
+    }
+}
+
+impl Default for FText {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl fmt::Display for FText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Writer<'f, 'b> {
+            f: &'f mut fmt::Formatter<'b>,
+            result: fmt::Result,
+        }
+
+        extern "C" fn write(user_data: *mut (), chars: StrView) {
+            let writer = unsafe { &mut *(user_data as *mut Writer) };
+            if writer.result.is_err() {
+                return;
+            }
+            for chunk in unsafe { chars.as_bytes() }.utf8_chunks() {
+                writer.result = writer.f.write_str(chunk.valid());
+                if writer.result.is_ok() && !chunk.invalid().is_empty() {
+                    writer.result = writer.f.write_char(char::REPLACEMENT_CHARACTER);
+                }
+                if writer.result.is_err() {
+                    return;
+                }
+            }
+        }
+
+        let mut writer = Writer { f, result: Ok(()) };
+        unsafe {
+            (cpp_get().text_to_string)(self, &mut writer as *mut Writer as *mut (), write);
+        }
+        writer.result
+    }
+}
+
+impl fmt::Debug for FText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FText").field(&self.to_string()).finish()
+    }
+}
+
+macro_rules! impl_from_for_format_argument {
+    ($($Ty:ty => $Variant:ident as $Target:ty,)*) => {
+        $(
+            impl From<$Ty> for FormatArgumentValue<'_> {
+                fn from(value: $Ty) -> Self {
+                    Self::$Variant(value as $Target)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_for_format_argument! {
+    i32 => Int as i64,
+    i64 => Int as i64,
+    u32 => UInt as u64,
+    u64 => UInt as u64,
+    f32 => Float as f32,
+    f64 => Double as f64,
+}
+
+impl<'a> From<&'a FText> for FormatArgumentValue<'a> {
+    fn from(value: &'a FText) -> Self {
+        Self::Text(value)
+    }
+}
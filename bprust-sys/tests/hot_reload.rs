@@ -0,0 +1,295 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    env::consts::{DLL_PREFIX, DLL_SUFFIX},
+    ffi::{CStr, c_char},
+    fs,
+    mem::size_of,
+    path::{Path, PathBuf},
+    process::Command,
+    ptr::{self, NonNull},
+    sync::Mutex,
+};
+
+use bprust_sys::{
+    FText, HotReloadCapability, LibraryEntry,
+    cpp_import::{
+        self, BPRustStatus, CPP_FUNCTION_TABLE_VERSION, Capability, CppFunctionTable,
+        FormatNamedArgument, StrView, custom_thunk::Handler,
+    },
+    init_library, persist_on_reload, prepare_reload, take_reload_state,
+};
+use libloading::Library;
+
+/// The storage of the plugin, which outlives the unloaded library.
+static STORAGE: Mutex<BTreeMap<Vec<u8>, Vec<u8>>> = Mutex::new(BTreeMap::new());
+
+unsafe extern "C" fn store(key: StrView, data: *const u8, len: usize) {
+    let key = unsafe { key.as_bytes() }.to_vec();
+    let data = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+    STORAGE.lock().unwrap().insert(key, data);
+}
+
+unsafe extern "C" fn take(
+    key: StrView,
+    user_data: *mut (),
+    write: extern "C" fn(user_data: *mut (), data: *const u8, len: usize),
+) -> bool {
+    let key = unsafe { key.as_bytes() };
+    let Some(data) = STORAGE.lock().unwrap().remove(key) else {
+        return false;
+    };
+    write(user_data, data.as_ptr(), data.len());
+    true
+}
+
+static HOT_RELOAD: HotReloadCapability = HotReloadCapability { store, take };
+
+/// Mirrors `EventsCapability` of `tests/reload_library`, which reports what the library does.
+#[repr(C)]
+struct EventsCapability {
+    record: unsafe extern "C" fn(event: StrView),
+}
+
+unsafe impl Capability for EventsCapability {
+    const NAME: &'static CStr = c"BPRustTest.Events.v1";
+}
+
+unsafe extern "C" fn record(event: StrView) {
+    let event = String::from_utf8(unsafe { event.as_bytes() }.to_vec()).unwrap();
+    EVENTS.with_borrow_mut(|events| events.push(event));
+}
+
+static EVENTS_CAPABILITY: EventsCapability = EventsCapability { record };
+
+// the library is called on the thread of its test, and so are the callbacks
+thread_local! {
+    static EVENTS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    /// Lookups that reached the function table.
+    static LOOKUPS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn take_events() -> Vec<String> {
+    EVENTS.take()
+}
+
+fn take_lookups() -> Vec<String> {
+    LOOKUPS.take()
+}
+
+unsafe extern "C" fn get_capability(name: *const c_char) -> *const () {
+    let name = unsafe { CStr::from_ptr(name) };
+    LOOKUPS
+        .with_borrow_mut(|lookups| lookups.push(format!("capability {}", name.to_str().unwrap())));
+    if name == HotReloadCapability::NAME {
+        &HOT_RELOAD as *const HotReloadCapability as *const ()
+    } else if name == EventsCapability::NAME {
+        &EVENTS_CAPABILITY as *const EventsCapability as *const ()
+    } else {
+        ptr::null()
+    }
+}
+
+// a class is only compared and passed back, never accessed
+unsafe extern "C" fn find_class(path: *const c_char) -> *mut () {
+    let path = unsafe { CStr::from_ptr(path) };
+    LOOKUPS.with_borrow_mut(|lookups| lookups.push(format!("class {}", path.to_str().unwrap())));
+    NonNull::dangling().as_ptr()
+}
+
+// the tests never touch UE objects or text
+unsafe extern "C" fn handle_custom_thunk(
+    _: &mut Handler,
+    _: *mut (),
+    _: extern "C" fn(*mut (), &mut Handler),
+    _: extern "C" fn(*mut (), *mut ()),
+) {
+    unreachable!()
+}
+unsafe extern "C" fn process_event(_: *mut (), _: *const c_char, _: *mut ()) -> i32 {
+    unreachable!()
+}
+unsafe extern "C" fn is_valid(_: *mut ()) -> bool {
+    unreachable!()
+}
+unsafe extern "C" fn object_is_a(_: *mut (), _: *mut ()) -> bool {
+    unreachable!()
+}
+unsafe extern "C" fn gc_root_add(_: *mut ()) -> u64 {
+    unreachable!()
+}
+unsafe extern "C" fn gc_root_get(_: u64) -> *mut () {
+    unreachable!()
+}
+unsafe extern "C" fn gc_root_remove(_: u64) {
+    unreachable!()
+}
+unsafe extern "C" fn text_from_str(_: *mut FText, _: StrView) {
+    unreachable!()
+}
+unsafe extern "C" fn text_find(_: *mut FText, _: StrView, _: StrView) -> bool {
+    unreachable!()
+}
+unsafe extern "C" fn text_format(
+    _: *mut FText,
+    _: *const FText,
+    _: *const FormatNamedArgument,
+    _: usize,
+) {
+    unreachable!()
+}
+unsafe extern "C" fn text_to_string(
+    _: *const FText,
+    _: *mut (),
+    _: extern "C" fn(*mut (), StrView),
+) {
+    unreachable!()
+}
+unsafe extern "C" fn text_clone(_: *mut FText, _: *const FText) {
+    unreachable!()
+}
+unsafe extern "C" fn text_drop(_: *mut FText) {
+    unreachable!()
+}
+
+fn fake_table() -> CppFunctionTable {
+    CppFunctionTable {
+        handle_custom_thunk,
+        process_event,
+        is_valid,
+        find_class,
+        object_is_a,
+        gc_root_add,
+        gc_root_get,
+        gc_root_remove,
+        text_from_string: text_from_str,
+        text_as_culture_invariant: text_from_str,
+        text_find,
+        text_format,
+        text_to_string,
+        text_clone,
+        text_drop,
+        get_capability: Some(get_capability),
+        object_get_class: None,
+        find_function: None,
+        process_event_with_function: None,
+        init_params: None,
+        destroy_params: None,
+        name_from_string: None,
+        latent_action_info: None,
+        latent_action_forget: None,
+        string_drop: None,
+        script_array_drop: None,
+        script_set_drop: None,
+        script_map_drop: None,
+        class_default_object: None,
+    }
+}
+
+fn init(table: &CppFunctionTable) {
+    let status = unsafe {
+        init_library(
+            c"hot_reload_test",
+            table,
+            CPP_FUNCTION_TABLE_VERSION,
+            size_of::<CppFunctionTable>(),
+            None,
+        )
+    };
+    assert_eq!(status, BPRustStatus::Ok);
+}
+
+#[test]
+fn state_is_kept_across_two_reloads() {
+    // nothing is stored before the function table is installed, and looking it up doesn't panic
+    assert_eq!(take_reload_state("count"), None);
+
+    let table = fake_table();
+    init(&table);
+    assert_eq!(take_reload_state("count"), None);
+
+    for count in 1..=2u32 {
+        persist_on_reload("count", move || count.to_le_bytes().to_vec());
+        assert_eq!(prepare_reload(), BPRustStatus::Ok);
+        assert_eq!(cpp_import::shutdown(), BPRustStatus::Ok);
+
+        init(&table);
+        let state = take_reload_state("count").expect("the state saved before the reload");
+        assert_eq!(state, count.to_le_bytes());
+        // the state is handed over once
+        assert_eq!(take_reload_state("count"), None);
+    }
+
+    assert_eq!(cpp_import::shutdown(), BPRustStatus::Ok);
+}
+
+/// Build `tests/reload_library` and return the path of the cdylib.
+fn build_reload_library() -> PathBuf {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("reload_library");
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/reload_library/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("running cargo");
+    assert!(status.success(), "building tests/reload_library failed");
+    target_dir
+        .join("debug")
+        .join(format!("{DLL_PREFIX}reload_library{DLL_SUFFIX}"))
+}
+
+#[test]
+fn library_is_unloaded_and_loaded_again_twice() {
+    let library_path = build_reload_library();
+    let shadow_dir = library_path.with_file_name("shadow");
+    fs::create_dir_all(&shadow_dir).unwrap();
+
+    let table = fake_table();
+    let mut starts = 0;
+    for load in 0..3 {
+        // like the plugin, every load uses a copy with a unique name, so it is a new image
+        let shadow_path = shadow_dir.join(format!("reload_library-{load}{DLL_SUFFIX}"));
+        fs::copy(&library_path, &shadow_path).unwrap();
+        let library = unsafe { Library::new(&shadow_path) }.unwrap();
+        let entry = unsafe {
+            let entry = library
+                .get::<extern "C" fn() -> *const LibraryEntry>(b"BPRustSys_reload_library_entry")
+                .unwrap();
+            &*entry()
+        };
+        assert_eq!(entry.abi_version, CPP_FUNCTION_TABLE_VERSION);
+
+        // initialized again before it is unloaded, the library keeps its statics and the
+        // caches of the previous initialization are outdated
+        for _ in 0..2 {
+            starts += 1;
+            let status = unsafe {
+                (entry.init)(
+                    &table,
+                    CPP_FUNCTION_TABLE_VERSION,
+                    size_of::<CppFunctionTable>(),
+                )
+            };
+            assert_eq!(status, BPRustStatus::Ok);
+            assert_eq!(take_events(), [format!("startup {starts}")]);
+            assert_eq!(
+                take_lookups(),
+                [
+                    "capability BPRust.HotReload.v1",
+                    "class /Script/Engine.Actor",
+                    "capability BPRustTest.Events.v1",
+                ]
+            );
+
+            assert_eq!((entry.prepare_reload)(), BPRustStatus::Ok);
+            assert_eq!((entry.shutdown)(), BPRustStatus::Ok);
+            assert_eq!(take_events(), ["shutdown"]);
+            assert_eq!(take_lookups(), [""; 0]);
+        }
+
+        library.close().unwrap();
+        fs::remove_file(&shadow_path).unwrap();
+    }
+}
@@ -0,0 +1,49 @@
+// loaded and unloaded by `tests/hot_reload.rs`, which provides the capability reporting what
+// the library does
+use std::ffi::CStr;
+
+use bprust_sys::{
+    StaticClassCache,
+    cpp_import::{Capability, CapabilityCache, StrView},
+    on_shutdown, persist_on_reload, take_reload_state,
+};
+
+bprust_sys::register_library! {
+    startup: startup,
+}
+
+/// Mirrored by `tests/hot_reload.rs`.
+#[repr(C)]
+pub struct EventsCapability {
+    pub record: unsafe extern "C" fn(event: StrView),
+}
+
+unsafe impl Capability for EventsCapability {
+    const NAME: &'static CStr = c"BPRustTest.Events.v1";
+}
+
+static EVENTS: CapabilityCache<EventsCapability> = CapabilityCache::new();
+
+static ACTOR_CLASS: StaticClassCache = StaticClassCache::new();
+
+fn record(event: &str) {
+    let events = EVENTS.get().expect("the test provides its events capability");
+    unsafe { (events.record)(event.into()) }
+}
+
+fn startup() {
+    let starts = take_reload_state("starts")
+        .map_or(0, |state| u32::from_le_bytes(state.try_into().unwrap()))
+        + 1;
+    persist_on_reload("starts", move || starts.to_le_bytes().to_vec());
+
+    // only the first lookup reaches the function table
+    for _ in 0..2 {
+        ACTOR_CLASS
+            .get_or_find(c"/Script/Engine.Actor")
+            .expect("the test provides the class");
+    }
+
+    record(&format!("startup {starts}"));
+    on_shutdown(|| record("shutdown"));
+}
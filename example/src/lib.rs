@@ -1,3 +1,8 @@
+// shipped as `ExampleLibrary.dll`, so the entry point is named after the file
+bprust_sys::register_library! {
+    name: "ExampleLibrary",
+}
+
 mod bp {
     #![allow(nonstandard_style)]
 